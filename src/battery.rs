@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::{Args, Subcommand};
+use proc_hunter::models_fn_packer::{BatteryHealth, CapacityProjection, HealthSample};
+use proc_hunter::models_fn_packer::{append_health_history, format_date, get_batteries};
+use proc_hunter::models_fn_packer::{project_capacity, read_health_history};
+use serde_json::json;
+
+use crate::or_exit;
+use crate::output::{OutputFormat, print_document};
+
+#[derive(Args)]
+pub struct BatteryArgs {
+    #[command(subcommand)]
+    command: BatteryCommand,
+}
+
+#[derive(Subcommand)]
+enum BatteryCommand {
+    /// Report the wear of every pack and record it in a history file
+    Health(HealthArgs),
+}
+
+#[derive(Args)]
+struct HealthArgs {
+    /// History file, defaults to battery_health.tsv in the user data directory
+    #[arg(long)]
+    history: Option<PathBuf>,
+
+    /// Do not append the current readings to the history file
+    #[arg(long, default_value_t = false)]
+    no_record: bool,
+
+    /// Fit a trend to the history and project when capacity crosses the threshold
+    #[arg(long, default_value_t = false)]
+    project: bool,
+
+    /// State of health, in percent, the projection looks for
+    #[arg(long, default_value_t = 80.0)]
+    threshold: f32,
+}
+
+pub fn run(args: &BatteryArgs, format: OutputFormat) {
+    match &args.command {
+        BatteryCommand::Health(args) => health(args, format),
+    }
+}
+
+fn health(args: &HealthArgs, format: OutputFormat) {
+    let history_path = args.history.clone().unwrap_or_else(default_history_path);
+    let packs: Vec<BatteryHealth> = or_exit(get_batteries())
+        .iter()
+        .map(BatteryHealth::from)
+        .collect();
+
+    if !args.no_record {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let samples: Vec<HealthSample> = packs
+            .iter()
+            .map(|pack| HealthSample::new(pack, now))
+            .collect();
+        if let Err(err) = append_health_history(&history_path, &samples) {
+            eprintln!("Cannot record to {}: {}", history_path.display(), err);
+        }
+    }
+    let projections = if args.project {
+        let history = or_exit(read_health_history(&history_path));
+        Some(project_capacity(&history, args.threshold / 100.0))
+    } else {
+        None
+    };
+
+    if format != OutputFormat::Text {
+        print_document(
+            format,
+            &json!({ "batteries": packs, "projections": projections }),
+        );
+        return;
+    }
+    for pack in &packs {
+        println!(
+            "Battery {}: {} ({})",
+            pack.index,
+            pack.battery,
+            pack.model.as_deref().unwrap_or("Unknown")
+        );
+        println!(
+            "   health: {:.1}% (wear {:.1}%)",
+            pack.state_of_health * 100.0,
+            pack.wear_level * 100.0
+        );
+        println!(
+            "   full: {:.2} Wh of {:.2} Wh design",
+            pack.energy_full / 3600.0,
+            pack.energy_full_design / 3600.0
+        );
+        match pack.cycle_count {
+            Some(cycle_count) => println!("   cycle count: {}", cycle_count),
+            None => println!("   cycle count: Unknown"),
+        }
+        println!();
+    }
+    if let Some(projections) = projections {
+        print_projections(&projections, args.threshold);
+    }
+}
+
+fn print_projections(projections: &[CapacityProjection], threshold: f32) {
+    println!("Projection to {}% state of health:", threshold);
+    if projections.is_empty() {
+        println!(
+            "   not enough history yet, it needs at least two readings taken at different times"
+        );
+    }
+    for projection in projections {
+        let outlook = match projection.crosses_at {
+            Some(timestamp) => format!("reaches {}% around {}", threshold, format_date(timestamp)),
+            None => "not degrading".to_string(),
+        };
+        println!(
+            "   {}: {:.1}% now, losing {:.1}%/year, {} ({} samples)",
+            projection.battery,
+            projection.state_of_health * 100.0,
+            projection.loss_per_year * 100.0,
+            outlook,
+            projection.samples
+        );
+    }
+}
+
+fn default_history_path() -> PathBuf {
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    data_dir.join("proc_hunter").join("battery_health.tsv")
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::io;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong while collecting system information.
+#[derive(Debug)]
+pub enum Error {
+    /// The battery backend could not be initialised or queried.
+    BatteryBackendUnavailable(battery::Error),
+    /// The backend works, but the machine has no battery.
+    NoBatteries,
+    /// The OS refused access; the payload says to what.
+    PermissionDenied(String),
+    /// The process exited, or never existed.
+    ProcessVanished(u32),
+    /// A thread panicked while holding the shared system lock.
+    LockPoisoned,
+    /// A hunt pattern is not a valid regular expression.
+    InvalidPattern(regex::Error),
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// A file or argument is not in the expected format.
+    Parse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BatteryBackendUnavailable(err) => {
+                write!(f, "battery backend unavailable: {}", err)
+            }
+            Error::NoBatteries => f.write_str("no battery found"),
+            Error::PermissionDenied(what) => write!(f, "permission denied: {}", what),
+            Error::ProcessVanished(pid) => write!(f, "process {} does not exist", pid),
+            Error::LockPoisoned => f.write_str("system information lock was poisoned"),
+            Error::InvalidPattern(err) => write!(f, "invalid pattern: {}", err),
+            Error::Io(err) => err.fmt(f),
+            Error::Parse(reason) => write!(f, "parse error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::BatteryBackendUnavailable(err) => Some(err),
+            Error::InvalidPattern(err) => Some(err),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<battery::Error> for Error {
+    fn from(err: battery::Error) -> Self {
+        let permission_denied = std::error::Error::source(&err)
+            .and_then(|source| source.downcast_ref::<io::Error>())
+            .is_some_and(|source| source.kind() == io::ErrorKind::PermissionDenied);
+        if permission_denied {
+            Error::PermissionDenied(format!("battery: {}", err))
+        } else {
+            Error::BatteryBackendUnavailable(err)
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<regex::Error> for Error {
+    fn from(err: regex::Error) -> Self {
+        Error::InvalidPattern(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn test_from_battery_error() {
+        let denied =
+            battery::Error::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(matches!(Error::from(denied), Error::PermissionDenied(_)));
+
+        let missing = battery::Error::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(matches!(
+            Error::from(missing),
+            Error::BatteryBackendUnavailable(_)
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::ProcessVanished(42).to_string(),
+            "process 42 does not exist"
+        );
+        assert_eq!(Error::NoBatteries.to_string(), "no battery found");
+    }
+}
@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use clap::{Args, ValueEnum};
+use proc_hunter::models_fn_packer::{HogKind, HogPolicy, KillOutcome, ProcessUsage, Signal};
+use proc_hunter::models_fn_packer::{MatchField, MatchMode, ProcessMatcher, ReniceOutcome};
+use proc_hunter::models_fn_packer::{is_same_process, renice, sample_hogs, send_signal};
+
+use crate::output::{OutputFormat, print_document};
+use crate::{confirm, format_bytes, or_exit, parse_duration, parse_size};
+
+#[derive(Args)]
+pub struct HogArgs {
+    /// What to rank processes by
+    #[arg(long, value_enum, default_value_t = HogKindArg::Cpu)]
+    by: HogKindArg,
+
+    /// How long to watch the processes
+    #[arg(long, value_parser = parse_duration, default_value = "5s")]
+    window: Duration,
+
+    /// Number of samples taken over the window
+    #[arg(long, default_value_t = 5)]
+    samples: u32,
+
+    /// Number of processes to report
+    #[arg(short = 'n', long, default_value_t = 10)]
+    limit: usize,
+
+    /// Processes above this mean CPU usage, in percent, are offenders
+    #[arg(long)]
+    cpu_limit: Option<f32>,
+
+    /// Processes whose RSS grows by more than this over the window, e.g.
+    /// 200MiB, are offenders
+    #[arg(long, value_parser = parse_size)]
+    memory_growth_limit: Option<u64>,
+
+    /// Processes reading and writing more than this over the window, e.g.
+    /// 1GiB, are offenders
+    #[arg(long, value_parser = parse_size)]
+    io_limit: Option<u64>,
+
+    /// What to do with offenders; without it they are only reported
+    #[arg(long, value_enum)]
+    action: Option<HogAction>,
+
+    /// Nice value offenders get with --action renice
+    #[arg(long, default_value_t = 10, allow_negative_numbers = true)]
+    nice: i32,
+
+    /// Never touch processes whose name matches this glob, on top of the
+    /// built-in list of system daemons (repeatable)
+    #[arg(long)]
+    allow: Vec<String>,
+
+    /// Let the action apply to processes owned by root
+    #[arg(long, default_value_t = false)]
+    include_root: bool,
+
+    /// Only list what the action would do
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Do not ask for confirmation
+    #[arg(short, long, default_value_t = false)]
+    yes: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HogKindArg {
+    Cpu,
+    Memory,
+    Io,
+}
+
+impl From<HogKindArg> for HogKind {
+    fn from(kind: HogKindArg) -> Self {
+        match kind {
+            HogKindArg::Cpu => HogKind::Cpu,
+            HogKindArg::Memory => HogKind::Memory,
+            HogKindArg::Io => HogKind::Io,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum HogAction {
+    /// Lower the scheduling priority
+    Renice,
+    /// Pause with SIGSTOP, resume later with `kill -s CONT`
+    Stop,
+    /// Terminate with SIGKILL
+    Kill,
+}
+
+pub fn run(args: &HogArgs, format: OutputFormat) {
+    let policy = build_policy(args);
+    let has_limits = policy.cpu_limit.is_some()
+        || policy.memory_growth_limit.is_some()
+        || policy.io_limit.is_some();
+    if args.action.is_some() && !has_limits {
+        eprintln!(
+            "--action needs at least one of --cpu-limit, --memory-growth-limit and --io-limit"
+        );
+        std::process::exit(2);
+    }
+
+    let mut report = or_exit(sample_hogs(args.window, args.samples, args.by.into()));
+    let offenders: Vec<ProcessUsage> = policy.offenders(&report).into_iter().cloned().collect();
+    report.truncate(args.limit);
+
+    if format != OutputFormat::Text {
+        print_document(format, &report);
+    } else {
+        print_report(&report, &policy, has_limits);
+    }
+
+    let Some(action) = args.action else {
+        return;
+    };
+    if offenders.is_empty() {
+        println!("No offender above the limits");
+        return;
+    }
+    let verb = match action {
+        HogAction::Renice => format!("Renice to {}", args.nice),
+        HogAction::Stop => "Stop".to_string(),
+        HogAction::Kill => "Kill".to_string(),
+    };
+    let listed: Vec<String> = offenders
+        .iter()
+        .map(|usage| format!("{} ({})", usage.pid, usage.name))
+        .collect();
+    let prompt = format!("{} {}?", verb, listed.join(", "));
+    if args.dry_run {
+        println!("Would {}", prompt.trim_end_matches('?').to_lowercase());
+        return;
+    }
+    if !args.yes && !confirm(&prompt) {
+        println!("Aborted");
+        std::process::exit(1);
+    }
+    // The prompt may have waited long enough for an offender to exit and its
+    // PID to be reused.
+    let mut failed = false;
+    let mut pids = Vec::new();
+    for usage in &offenders {
+        if or_exit(is_same_process(usage)) {
+            pids.push(usage.pid);
+        } else {
+            println!("{}: skipped, no longer the sampled process", usage.pid);
+            failed = true;
+        }
+    }
+    match action {
+        HogAction::Renice => {
+            for report in or_exit(renice(&pids, args.nice)) {
+                println!("{}: {}", report.pid, report.outcome);
+                failed |= report.outcome != ReniceOutcome::Reniced;
+            }
+        }
+        HogAction::Stop | HogAction::Kill => {
+            let signal = if action == HogAction::Stop {
+                Signal::Stop
+            } else {
+                Signal::Kill
+            };
+            for report in or_exit(send_signal(&pids, signal)) {
+                println!("{}: {}", report.pid, report.outcome);
+                failed |= report.outcome != KillOutcome::Delivered;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn build_policy(args: &HogArgs) -> HogPolicy {
+    let mut policy = HogPolicy::new();
+    policy.cpu_limit = args.cpu_limit;
+    policy.memory_growth_limit = args.memory_growth_limit;
+    policy.io_limit = args.io_limit;
+    policy.protect_root = !args.include_root;
+    for pattern in &args.allow {
+        match ProcessMatcher::new(pattern, MatchMode::Glob, false) {
+            Ok(matcher) => policy = policy.allow(matcher.with_fields(&[MatchField::Name])),
+            Err(err) => {
+                eprintln!("Invalid pattern: {}", err);
+                std::process::exit(2);
+            }
+        }
+    }
+    policy
+}
+
+fn print_report(report: &[ProcessUsage], policy: &HogPolicy, has_limits: bool) {
+    println!(
+        "{:>7} {:<12} {:>6} {:>9} {:>10} {:>9} {:>9}  COMMAND",
+        "PID", "USER", "CPU%", "RSS", "GROWTH", "READ", "WRITTEN"
+    );
+    for usage in report {
+        let mark = if !has_limits || !policy.exceeds(usage) {
+            ""
+        } else if policy.is_protected(usage) {
+            "  (over limit, protected)"
+        } else {
+            "  (over limit)"
+        };
+        println!(
+            "{:>7} {:<12} {:>6.1} {:>9} {:>10} {:>9} {:>9}  {}{}",
+            usage.pid,
+            usage.user.as_deref().unwrap_or("?"),
+            usage.cpu_usage,
+            format_bytes(usage.memory),
+            format_growth(usage.memory_growth),
+            format_bytes(usage.read_bytes),
+            format_bytes(usage.written_bytes),
+            usage.command,
+            mark
+        );
+        if !usage.parents.is_empty() {
+            let chain: Vec<String> = usage
+                .parents
+                .iter()
+                .map(|(pid, name)| format!("{} ({})", name, pid))
+                .collect();
+            println!("{:>7} parents: {}", "", chain.join(" < "));
+        }
+    }
+}
+
+fn format_growth(bytes: i64) -> String {
+    if bytes < 0 {
+        format!("-{}", format_bytes(bytes.unsigned_abs()))
+    } else {
+        format!("+{}", format_bytes(bytes as u64))
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use clap::{Args, ValueEnum};
+use proc_hunter::models_fn_packer::{IoSortKey, ProcessIoRow, sample_process_io, sort_io_rows};
+
+use crate::output::{OutputFormat, print_document};
+use crate::{MatchOptions, format_bytes, or_exit, parse_interval};
+
+#[derive(Args)]
+pub struct IoArgs {
+    /// Window over which read and write rates are measured
+    #[arg(long, value_parser = parse_interval, default_value = "1s")]
+    interval: Duration,
+
+    /// Column to sort by
+    #[arg(short, long, value_enum, default_value_t = IoSortKeyArg::Total)]
+    sort: IoSortKeyArg,
+
+    /// Sort in ascending order instead of descending
+    #[arg(long, default_value_t = false)]
+    ascending: bool,
+
+    /// Maximum number of rows, 0 for no limit
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+
+    /// Only show processes matching this pattern
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    #[command(flatten)]
+    match_options: MatchOptions,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum IoSortKeyArg {
+    Pid,
+    /// Read rate
+    Read,
+    /// Write rate
+    Write,
+    /// Read plus write rate
+    Total,
+    /// Bytes read since start
+    ReadTotal,
+    /// Bytes written since start
+    WriteTotal,
+}
+
+impl From<IoSortKeyArg> for IoSortKey {
+    fn from(key: IoSortKeyArg) -> Self {
+        match key {
+            IoSortKeyArg::Pid => IoSortKey::Pid,
+            IoSortKeyArg::Read => IoSortKey::Read,
+            IoSortKeyArg::Write => IoSortKey::Write,
+            IoSortKeyArg::Total => IoSortKey::Total,
+            IoSortKeyArg::ReadTotal => IoSortKey::TotalRead,
+            IoSortKeyArg::WriteTotal => IoSortKey::TotalWritten,
+        }
+    }
+}
+
+pub fn run(args: &IoArgs, format: OutputFormat) {
+    let matcher = args
+        .pattern
+        .as_ref()
+        .map(|pattern| args.match_options.to_matcher(pattern));
+    let mut rows = or_exit(sample_process_io(args.interval, matcher.as_ref()));
+    rows.retain(|row| row.pid != std::process::id());
+    sort_io_rows(&mut rows, args.sort.into(), !args.ascending);
+    if args.limit > 0 {
+        rows.truncate(args.limit);
+    }
+
+    if format != OutputFormat::Text {
+        print_document(format, &rows);
+        return;
+    }
+    if rows.is_empty() {
+        println!("No process to show");
+        return;
+    }
+    print_io_table(&rows);
+}
+
+fn print_io_table(rows: &[ProcessIoRow]) {
+    println!(
+        "{:>7} {:<12} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}  COMMAND",
+        "PID", "USER", "READ/s", "WRITE/s", "READ", "WRITTEN", "SYSCR", "SYSCW", "CANCELLED"
+    );
+    for row in rows {
+        // The syscall counters are only readable for our own processes
+        // unless running as root.
+        let (syscr, syscw, cancelled) = match &row.io {
+            Some(io) => (
+                io.syscr.to_string(),
+                io.syscw.to_string(),
+                format_bytes(io.cancelled_write_bytes),
+            ),
+            None => ("-".to_string(), "-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:>7} {:<12} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}  {}",
+            row.pid,
+            row.user.as_deref().unwrap_or("?"),
+            format_bytes(row.read_rate as u64),
+            format_bytes(row.write_rate as u64),
+            format_bytes(row.total_read_bytes),
+            format_bytes(row.total_written_bytes),
+            syscr,
+            syscw,
+            cancelled,
+            row.command
+        );
+    }
+}
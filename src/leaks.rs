@@ -0,0 +1,117 @@
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use clap::Args;
+use proc_hunter::models_fn_packer::sample_memory_trends;
+use proc_hunter::models_fn_packer::{LeakCriteria, MIN_TREND_SAMPLES, MemoryTrend};
+
+use crate::output::{OutputFormat, print_document};
+use crate::{format_bytes, format_duration, or_exit, parse_duration, parse_interval, parse_size};
+
+#[derive(Args)]
+pub struct LeaksArgs {
+    /// How long to watch the processes
+    #[arg(long, value_parser = parse_duration, default_value = "10m")]
+    duration: Duration,
+
+    /// Time between samples
+    #[arg(long, value_parser = parse_interval, default_value = "10s")]
+    interval: Duration,
+
+    /// Minimum RSS growth per hour to report a process, e.g. 5MiB
+    #[arg(long, value_parser = parse_size, default_value = "1MiB")]
+    min_rate: u64,
+
+    /// Minimum R² of the linear fit, between 0 and 1
+    #[arg(long, default_value_t = 0.9)]
+    min_r2: f64,
+
+    /// Report every process, not only the leaking ones
+    #[arg(long, default_value_t = false)]
+    all: bool,
+
+    /// Number of processes to report
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+}
+
+pub fn run(args: &LeaksArgs, format: OutputFormat) {
+    let expected = args.duration.as_nanos().div_ceil(args.interval.as_nanos()) + 1;
+    if expected < MIN_TREND_SAMPLES as u128 {
+        eprintln!(
+            "--duration must cover at least {} samples",
+            MIN_TREND_SAMPLES
+        );
+        std::process::exit(2);
+    }
+    let show_progress = std::io::stderr().is_terminal();
+    let trends = or_exit(sample_memory_trends(
+        args.duration,
+        args.interval,
+        |taken| {
+            if show_progress {
+                eprint!("\rSampled {}/{}", taken, expected);
+                let _ = std::io::stderr().flush();
+            }
+        },
+    ));
+    if show_progress {
+        eprintln!();
+    }
+
+    let criteria = LeakCriteria {
+        min_growth_rate: args.min_rate as f64 / 3600.0,
+        min_r_squared: args.min_r2,
+    };
+    let mut trends: Vec<MemoryTrend> = trends
+        .into_iter()
+        .filter(|trend| args.all || trend.is_leak(&criteria))
+        .collect();
+    trends.truncate(args.limit);
+
+    if format != OutputFormat::Text {
+        print_document(format, &trends);
+        return;
+    }
+    if trends.is_empty() {
+        println!(
+            "No process grew by more than {}/h over {}",
+            format_bytes(args.min_rate),
+            format_duration(args.duration.as_secs())
+        );
+        return;
+    }
+    println!(
+        "{:>7} {:>9} {:>11} {:>11} {:>5} {:>10}  COMMAND",
+        "PID", "RSS", "RSS/h", "VIRT/h", "R²", "EXHAUSTS"
+    );
+    for trend in &trends {
+        println!(
+            "{:>7} {:>9} {:>11} {:>11} {:>5.2} {:>10}  {}{}",
+            trend.pid,
+            format_bytes(trend.memory),
+            format_rate(trend.growth_rate),
+            format_rate(trend.virtual_growth_rate),
+            trend.r_squared,
+            trend
+                .time_to_exhaustion
+                .map_or("-".to_string(), format_duration),
+            trend.command,
+            if args.all && trend.is_leak(&criteria) {
+                "  (leak)"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+// Bytes per second, shown per hour.
+fn format_rate(bytes_per_second: f64) -> String {
+    let per_hour = bytes_per_second * 3600.0;
+    if per_hour < 0.0 {
+        format!("-{}", format_bytes(-per_hour as u64))
+    } else {
+        format!("+{}", format_bytes(per_hour as u64))
+    }
+}
@@ -1 +1,4 @@
+mod error;
 pub mod models_fn_packer;
+
+pub use error::{Error, Result};
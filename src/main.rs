@@ -1,14 +1,55 @@
-use clap::{Args, Parser, Subcommand};
-use proc_hunter::models_fn_packer::get_battery_brief_info;
-use proc_hunter::models_fn_packer::get_cpu_brief_info_with_1_ms_sleep;
-use proc_hunter::models_fn_packer::get_memory_brief_info;
-use proc_hunter::models_fn_packer::get_processes_brief_info;
+mod battery;
+mod hog;
+mod io;
+mod leaks;
+mod mem;
+mod output;
+mod record;
+mod replay;
+mod serve;
+mod top;
+mod tree;
+mod tui;
+mod watch;
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use proc_hunter::models_fn_packer::ProcMemory;
+use proc_hunter::models_fn_packer::hunt_processes;
+use proc_hunter::models_fn_packer::sample_battery_time_with;
+use proc_hunter::models_fn_packer::{BatteryDetails, BatteryInfo, BatteryTimeEstimates};
+use proc_hunter::models_fn_packer::{BatteryRateSmoother, TimeEstimate};
+use proc_hunter::models_fn_packer::{CpuInfo, MemoryInfo, ProcessSummary, Snapshot};
+use proc_hunter::models_fn_packer::{DiskInfo, get_disk_info};
+use proc_hunter::models_fn_packer::{KillOutcome, is_signal_supported, parse_signal};
+use proc_hunter::models_fn_packer::{LiveSource, ProcfsSource, SystemSource};
+use proc_hunter::models_fn_packer::{MatchField, MatchMode, ProcessMatch, ProcessMatcher};
+use proc_hunter::models_fn_packer::{NetworkInfo, get_network_info, sample_network_info};
+use proc_hunter::models_fn_packer::{SensorReading, cpu_core_id, get_sensor_info};
+use proc_hunter::models_fn_packer::{send_signal, signal_name};
+use serde_json::json;
+
+use output::{OutputFormat, print_document};
 
 #[derive(Parser)]
 #[command(name = "proc_hunter")]
 #[command(version = "1.0")]
 #[command(about = "A useless process hunter", long_about = None)]
 struct Cli {
+    /// Output format of info, hunt, hog, io, leaks, mem, battery and query
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Read CPU, memory and processes from this procfs tree instead of the
+    /// running system; only info, tree, mem, record, serve and watch accept
+    /// it
+    #[arg(long, global = true)]
+    proc_root: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -17,6 +58,54 @@ struct Cli {
 enum Commands {
     /// Get brief information about the computer
     Info(InfoArgs),
+    /// Find processes by name, command line or executable path
+    Hunt(HuntArgs),
+    /// Send a signal to processes
+    Kill(KillArgs),
+    /// Find the processes hogging CPU, memory or disk, and optionally rein
+    /// them in
+    Hog(hog::HogArgs),
+    /// Show per-process disk read and write rates and I/O counters
+    Io(io::IoArgs),
+    /// Watch processes for steadily growing memory
+    Leaks(leaks::LeaksArgs),
+    /// Break down the memory of processes into PSS, USS, shared and swap
+    /// (Linux)
+    Mem(mem::MemArgs),
+    /// Show a periodically refreshed process table
+    Top(top::TopArgs),
+    /// Full-screen interactive dashboard
+    Tui(tui::TuiArgs),
+    /// Show processes as a tree built from their parent links
+    Tree(tree::TreeArgs),
+    /// Battery reports
+    Battery(battery::BatteryArgs),
+    /// Write periodic snapshots of the system to a log file
+    Record(record::RecordArgs),
+    /// Show a recorded log as info, top or tree, snapshot by snapshot
+    Replay(replay::ReplayArgs),
+    /// Print the CPU and memory history of one process from a recorded log
+    Query(replay::QueryArgs),
+    /// Serve system metrics for Prometheus over HTTP
+    Serve(serve::ServeArgs),
+    /// Evaluate alerting rules and notify when they fire or resolve
+    Watch(watch::WatchArgs),
+}
+
+impl Commands {
+    /// Whether the command reads the system through `--proc-root`. The
+    /// others talk to the running system or a log directly.
+    fn reads_proc_root(&self) -> bool {
+        matches!(
+            self,
+            Commands::Info(_)
+                | Commands::Tree(_)
+                | Commands::Mem(_)
+                | Commands::Record(_)
+                | Commands::Serve(_)
+                | Commands::Watch(_)
+        )
+    }
 }
 
 #[derive(Args)]
@@ -32,32 +121,451 @@ struct InfoArgs {
 
     #[arg(long, default_value_t = false)]
     process: bool,
+
+    /// List mounted filesystems with their space and inode usage
+    #[arg(long, default_value_t = false)]
+    disk: bool,
+
+    /// With --disk, also list pseudo filesystems such as proc and tmpfs
+    #[arg(long, default_value_t = false, requires = "disk")]
+    all: bool,
+
+    /// List network interfaces with their addresses and traffic counters
+    #[arg(long, default_value_t = false)]
+    network: bool,
+
+    /// With --network, also measure the throughput of every interface
+    #[arg(long, default_value_t = false, requires = "network")]
+    throughput: bool,
+
+    /// Read CPU, drive and battery temperatures; with --cpu, core
+    /// temperatures are shown next to core frequencies
+    #[arg(long, default_value_t = false)]
+    sensors: bool,
+
+    /// Report every battery separately instead of summing them
+    #[arg(long, default_value_t = false)]
+    detailed: bool,
+
+    /// Window over which CPU usage, battery rates and network throughput are
+    /// measured, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    sample_ms: u64,
 }
 
-fn print_battery_brief_info() {
-    let (sum_energy, sum_energy_full) = get_battery_brief_info().unwrap();
-    println!("Battery sum energy: {}", sum_energy);
-    println!("Battery sum energy full: {}", sum_energy_full);
-    println!("");
+#[derive(Args)]
+struct MatchOptions {
+    /// How the pattern is compared
+    #[arg(long, value_enum, default_value_t = MatchModeArg::Exact)]
+    mode: MatchModeArg,
+
+    /// Ignore case when matching
+    #[arg(short = 'i', long, default_value_t = false)]
+    ignore_case: bool,
+
+    /// Match on the process name
+    #[arg(long, default_value_t = false)]
+    name: bool,
+
+    /// Match on the full command line
+    #[arg(long, default_value_t = false)]
+    cmd: bool,
+
+    /// Match on the executable path
+    #[arg(long, default_value_t = false)]
+    exe: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MatchModeArg {
+    Exact,
+    Glob,
+    Regex,
 }
 
-fn print_cpu_brief_info() {
-    let (physical_num, cpu_num, global_usage, cpus) = get_cpu_brief_info_with_1_ms_sleep();
-    println!("{} cpus on {} physical core", cpu_num, physical_num);
-    println!("Global CPU usage: {}", global_usage);
-    println!("");
-    for cpu in cpus {
-        // cpu: (cpu_brand, cpu_name, cpu_frequency, cpu_usage)
-        println!("{} ({}):", cpu.1, cpu.0);
-        println!("   frequency: {}", cpu.2);
-        println!("   usage: {}", cpu.3);
-        println!("");
+impl MatchOptions {
+    fn to_matcher(&self, pattern: &str) -> ProcessMatcher {
+        let mode = match self.mode {
+            MatchModeArg::Exact => MatchMode::Exact,
+            MatchModeArg::Glob => MatchMode::Glob,
+            MatchModeArg::Regex => MatchMode::Regex,
+        };
+        let mut fields = Vec::new();
+        if self.name {
+            fields.push(MatchField::Name);
+        }
+        if self.cmd {
+            fields.push(MatchField::Cmd);
+        }
+        if self.exe {
+            fields.push(MatchField::Exe);
+        }
+        match ProcessMatcher::new(pattern, mode, self.ignore_case) {
+            Ok(matcher) => matcher.with_fields(&fields),
+            Err(err) => {
+                eprintln!("Invalid pattern: {}", err);
+                std::process::exit(2);
+            }
+        }
     }
 }
 
-fn print_memory_brief_info() {
-    let (total_memory, free_memory, used_memory, total_swap, free_swap, used_swap) =
-        get_memory_brief_info();
+#[derive(Args)]
+struct HuntArgs {
+    /// Pattern to look for
+    pattern: String,
+
+    #[command(flatten)]
+    match_options: MatchOptions,
+
+    /// Add PSS and USS columns, read from /proc/<pid>/smaps_rollup (Linux)
+    #[arg(long, default_value_t = false)]
+    pss: bool,
+}
+
+#[derive(Args)]
+struct KillArgs {
+    /// PIDs of the processes to signal
+    #[arg(required_unless_present = "pattern", conflicts_with = "pattern")]
+    pids: Vec<u32>,
+
+    /// Signal every process matching this pattern instead of explicit PIDs
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    #[command(flatten)]
+    match_options: MatchOptions,
+
+    /// Signal to send, e.g. TERM, KILL or HUP
+    #[arg(short, long, default_value = "TERM")]
+    signal: String,
+
+    /// Only list the processes that would be signalled
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Do not ask for confirmation
+    #[arg(short, long, default_value_t = false)]
+    yes: bool,
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{}", bytes, UNITS[0])
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+/// Parses durations like `500ms`, `1s`, `5m` or `2h`. A bare number is in
+/// seconds.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration: {:?}", text))?;
+    let seconds = match unit {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        "d" => value * 86_400.0,
+        _ => {
+            return Err(format!(
+                "unknown duration unit {:?}, use ms, s, m, h or d",
+                unit
+            ));
+        }
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("duration out of range: {:?}", text))
+}
+
+/// Like [`parse_duration`], for the time between two samples, which cannot be
+/// zero.
+fn parse_interval(text: &str) -> Result<Duration, String> {
+    let interval = parse_duration(text)?;
+    if interval.is_zero() {
+        return Err("interval must be positive".to_string());
+    }
+    Ok(interval)
+}
+
+/// Parses sizes like `512MiB` or `4GiB`. A bare number is in bytes.
+fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size: {:?}", text))?;
+    let factor: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        unit => {
+            return Err(format!(
+                "unknown size unit {:?}, use B, KiB, MiB, GiB or TiB",
+                unit
+            ));
+        }
+    };
+    let bytes = value * factor as f64;
+    // `as` would silently saturate.
+    if !bytes.is_finite() || bytes > u64::MAX as f64 {
+        return Err(format!("size out of range: {:?}", text));
+    }
+    Ok(bytes as u64)
+}
+
+// Prints the error and exits, for commands that cannot do anything without
+// the data.
+fn or_exit<T>(result: proc_hunter::Result<T>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    })
+}
+
+fn print_not_available(section: &str, err: &proc_hunter::Error) {
+    println!("{}: not available ({})", section, err);
+    println!();
+}
+
+/// What `info` reports, read from a live source or from recorded
+/// snapshots. Sections that were not asked for are `None`.
+struct InfoData {
+    batteries: Option<proc_hunter::Result<(Vec<BatteryDetails>, BatteryTimeEstimates)>>,
+    cpu: Option<proc_hunter::Result<CpuInfo>>,
+    memory: Option<proc_hunter::Result<MemoryInfo>>,
+    processes: Option<proc_hunter::Result<ProcessSummary>>,
+    disks: Option<proc_hunter::Result<Vec<DiskInfo>>>,
+    networks: Option<Vec<NetworkInfo>>,
+    sensors: Option<proc_hunter::Result<Vec<SensorReading>>>,
+}
+
+// Battery readings taken over the sampling window to smooth the rate.
+const BATTERY_SAMPLES: usize = 5;
+
+impl InfoData {
+    fn collect(args: &InfoArgs, source: &mut dyn SystemSource) -> Self {
+        // The opt-in sections are left out of the default report.
+        let all = !(args.battery
+            || args.cpu
+            || args.memory
+            || args.process
+            || args.disk
+            || args.network
+            || args.sensors);
+        let window = Duration::from_millis(args.sample_ms);
+        InfoData {
+            batteries: (all || args.battery)
+                .then(|| sample_battery_time_with(|| source.batteries(), window, BATTERY_SAMPLES)),
+            cpu: (all || args.cpu).then(|| source.sample_cpu(window)),
+            memory: (all || args.memory).then(|| source.memory()),
+            processes: (all || args.process).then(|| source.process_summary()),
+            disks: args.disk.then(|| get_disk_info(args.all)),
+            networks: args.network.then(|| {
+                if args.throughput {
+                    sample_network_info(window)
+                } else {
+                    get_network_info()
+                }
+            }),
+            sensors: args.sensors.then(get_sensor_info),
+        }
+    }
+
+    /// Every section of a recorded snapshot. Battery time is estimated from
+    /// `smoother`, which should have seen the snapshots up to this one.
+    fn from_snapshot(snapshot: &Snapshot, smoother: &BatteryRateSmoother) -> Self {
+        let batteries = if snapshot.batteries.is_empty() {
+            Err(proc_hunter::Error::NoBatteries)
+        } else {
+            let estimates = smoother.estimate(&snapshot.batteries);
+            Ok((snapshot.batteries.clone(), estimates))
+        };
+        InfoData {
+            batteries: Some(batteries),
+            cpu: Some(Ok(snapshot.cpu.clone())),
+            memory: Some(Ok(snapshot.memory)),
+            processes: Some(Ok(ProcessSummary::from_rows(&snapshot.processes))),
+            disks: None,
+            networks: None,
+            sensors: None,
+        }
+    }
+}
+
+fn print_info(info: &InfoData, detailed: bool) {
+    match &info.batteries {
+        Some(Ok((batteries, estimates))) if detailed => {
+            print_battery_detailed_info(batteries, estimates)
+        }
+        Some(Ok((batteries, estimates))) => {
+            print_battery_brief_info(&BatteryInfo::from_batteries(batteries), &estimates.combined)
+        }
+        Some(Err(err)) => print_not_available("Battery", err),
+        None => {}
+    }
+    match &info.cpu {
+        Some(Ok(cpu)) => {
+            let sensors = match &info.sensors {
+                Some(Ok(sensors)) => sensors.as_slice(),
+                _ => &[],
+            };
+            print_cpu_brief_info(cpu, sensors)
+        }
+        Some(Err(err)) => print_not_available("CPU", err),
+        None => {}
+    }
+    match &info.memory {
+        Some(Ok(memory)) => print_memory_brief_info(memory),
+        Some(Err(err)) => print_not_available("Memory", err),
+        None => {}
+    }
+    match &info.processes {
+        Some(Ok(summary)) => print_process_brief_info(summary),
+        Some(Err(err)) => print_not_available("Processes", err),
+        None => {}
+    }
+    match &info.disks {
+        Some(Ok(disks)) => print_disk_info(disks),
+        Some(Err(err)) => print_not_available("Disks", err),
+        None => {}
+    }
+    if let Some(networks) = &info.networks {
+        print_network_info(networks);
+    }
+    match &info.sensors {
+        Some(Ok(sensors)) => print_sensor_info(sensors),
+        Some(Err(err)) => print_not_available("Temperatures", err),
+        None => {}
+    }
+}
+
+fn print_battery_brief_info(battery: &BatteryInfo, estimate: &TimeEstimate) {
+    println!("Battery sum energy: {}", battery.energy);
+    println!("Battery sum energy full: {}", battery.energy_full);
+    println!("Battery time: {}", format_time_estimate(estimate));
+    println!();
+}
+
+fn format_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn format_time_estimate(estimate: &TimeEstimate) -> String {
+    match (estimate.time_to_empty, estimate.time_to_full) {
+        (Some(seconds), _) => format!(
+            "{} to empty at {:.2} W",
+            format_duration(seconds),
+            estimate.smoothed_rate
+        ),
+        (_, Some(seconds)) => format!(
+            "{} to full at {:.2} W",
+            format_duration(seconds),
+            estimate.smoothed_rate
+        ),
+        (None, None) => format!("unknown ({})", estimate.state),
+    }
+}
+
+fn print_battery_detailed_info(batteries: &[BatteryDetails], estimates: &BatteryTimeEstimates) {
+    for battery in batteries {
+        println!(
+            "Battery {}: {} {}",
+            battery.index,
+            battery.vendor.as_deref().unwrap_or("Unknown"),
+            battery.model.as_deref().unwrap_or("Unknown")
+        );
+        println!(
+            "   serial number: {}",
+            battery.serial_number.as_deref().unwrap_or("Unknown")
+        );
+        println!("   technology: {}", battery.technology);
+        println!("   state: {}", battery.state);
+        println!(
+            "   charge: {:.1}% ({:.2} Wh of {:.2} Wh)",
+            battery.state_of_charge * 100.0,
+            battery.energy / 3600.0,
+            battery.energy_full / 3600.0
+        );
+        println!(
+            "   health: {:.1}% ({:.2} Wh of {:.2} Wh design)",
+            battery.state_of_health * 100.0,
+            battery.energy_full / 3600.0,
+            battery.energy_full_design / 3600.0
+        );
+        println!("   energy rate: {:.2} W", battery.energy_rate);
+        println!("   voltage: {:.2} V", battery.voltage);
+        match battery.cycle_count {
+            Some(cycle_count) => println!("   cycle count: {}", cycle_count),
+            None => println!("   cycle count: Unknown"),
+        }
+        if let Some(estimate) = estimates
+            .batteries
+            .iter()
+            .find(|estimate| estimate.index == Some(battery.index))
+        {
+            println!("   time: {}", format_time_estimate(estimate));
+        }
+        println!();
+    }
+    println!("Pack time: {}", format_time_estimate(&estimates.combined));
+    println!();
+}
+
+fn print_cpu_brief_info(cpu: &CpuInfo, sensors: &[SensorReading]) {
+    println!(
+        "{} cpus on {} physical core",
+        cpu.logical_core_count(),
+        cpu.physical_core_count.unwrap_or(0)
+    );
+    println!("Global CPU usage: {}", cpu.global_usage);
+    println!();
+    for core in &cpu.cores {
+        println!("{} ({}):", core.name, core.brand);
+        println!("   frequency: {}", core.frequency);
+        println!("   usage: {}", core.usage);
+        let reading = cpu_core_id(&core.name)
+            .and_then(|core_id| sensors.iter().find(|reading| reading.core == Some(core_id)));
+        if let Some(reading) = reading {
+            println!("   temperature: {}", format_temperature(reading));
+        }
+        println!();
+    }
+}
+
+fn print_memory_brief_info(memory: &MemoryInfo) {
+    let MemoryInfo {
+        total_memory,
+        free_memory,
+        used_memory,
+        total_swap,
+        free_swap,
+        used_swap,
+        ..
+    } = *memory;
     println!("Total memory: {}bytes", total_memory);
     println!(
         "Free memory: {}bytes ({:.2}%)",
@@ -69,7 +577,7 @@ fn print_memory_brief_info() {
         used_memory,
         (used_memory as f32 / total_memory as f32) * 100.0
     );
-    println!("");
+    println!();
     println!("Total swap: {}bytes", total_swap);
     println!(
         "Free swap: {}bytes ({:.2}%)",
@@ -81,56 +589,378 @@ fn print_memory_brief_info() {
         used_swap,
         (used_swap as f32 / total_swap as f32) * 100.0
     );
-    println!("");
+    println!();
+}
+
+fn print_process_brief_info(summary: &ProcessSummary) {
+    println!("Process number: {}", summary.process_count);
+    println!("Root process: {}", summary.root_process_count);
+    println!();
+}
+
+fn print_disk_info(disks: &[DiskInfo]) {
+    println!(
+        "{:<24} {:<8} {:>9} {:>9} {:>9} {:>5} {:>10} {:>6} {:<12}  MOUNTED ON",
+        "DEVICE", "TYPE", "SIZE", "USED", "AVAIL", "USE%", "INODES", "IUSE%", "FLAGS"
+    );
+    for disk in disks {
+        let mut flags = Vec::new();
+        if disk.is_read_only {
+            flags.push("ro");
+        }
+        if disk.is_removable {
+            flags.push("removable");
+        }
+        println!(
+            "{:<24} {:<8} {:>9} {:>9} {:>9} {:>4.0}% {:>10} {:>6} {:<12}  {}",
+            disk.device,
+            disk.file_system,
+            format_bytes(disk.total_space),
+            format_bytes(disk.used_space),
+            format_bytes(disk.available_space),
+            disk.usage(),
+            disk.total_inodes,
+            disk.inode_usage()
+                .map_or("-".to_string(), |usage| format!("{:.0}%", usage)),
+            if flags.is_empty() {
+                "-".to_string()
+            } else {
+                flags.join(",")
+            },
+            disk.mount_point
+        );
+    }
+    println!();
+}
+
+fn print_network_info(networks: &[NetworkInfo]) {
+    for network in networks {
+        println!(
+            "{} ({}, mtu {}):",
+            network.name,
+            network.mac_address.as_deref().unwrap_or("no MAC"),
+            network.mtu
+        );
+        if !network.addresses.is_empty() {
+            println!("   addresses: {}", network.addresses.join(", "));
+        }
+        println!(
+            "   received: {} in {} packets, {} errors",
+            format_bytes(network.received_bytes),
+            network.received_packets,
+            network.receive_errors
+        );
+        println!(
+            "   transmitted: {} in {} packets, {} errors",
+            format_bytes(network.transmitted_bytes),
+            network.transmitted_packets,
+            network.transmit_errors
+        );
+        if let (Some(receive_rate), Some(transmit_rate)) =
+            (network.receive_rate, network.transmit_rate)
+        {
+            println!(
+                "   throughput: {}/s in, {}/s out",
+                format_bytes(receive_rate as u64),
+                format_bytes(transmit_rate as u64)
+            );
+        }
+        println!();
+    }
+}
+
+fn print_sensor_info(sensors: &[SensorReading]) {
+    if sensors.is_empty() {
+        println!("Temperatures: no sensor found");
+        println!();
+        return;
+    }
+    println!("Temperatures:");
+    for reading in sensors {
+        let chip = match &reading.device {
+            Some(device) => format!("{} ({})", reading.chip, device),
+            None => reading.chip.clone(),
+        };
+        println!(
+            "   {} {}: {}",
+            chip,
+            reading.label,
+            format_temperature(reading)
+        );
+    }
+    println!();
+}
+
+fn format_temperature(reading: &SensorReading) -> String {
+    let mut text = format!("{:.1}°C", reading.temperature);
+    let thresholds: Vec<String> = [("max", reading.max), ("critical", reading.critical)]
+        .into_iter()
+        .filter_map(|(name, value)| Some(format!("{} {:.1}°C", name, value?)))
+        .collect();
+    if !thresholds.is_empty() {
+        text.push_str(&format!(" ({})", thresholds.join(", ")));
+    }
+    text
+}
+
+// Unavailable sections are reported as null.
+fn print_info_document(info: &InfoData, detailed: bool, format: OutputFormat) {
+    let mut document = serde_json::Map::new();
+    if let Some(batteries) = &info.batteries {
+        let batteries = batteries.as_ref().ok();
+        let battery = if detailed {
+            json!(batteries.map(|(batteries, _)| batteries))
+        } else {
+            json!(batteries.map(|(batteries, _)| BatteryInfo::from_batteries(batteries)))
+        };
+        document.insert("battery".to_string(), battery);
+        document.insert(
+            "battery_time".to_string(),
+            json!(batteries.map(|(_, estimates)| estimates)),
+        );
+    }
+    if let Some(cpu) = &info.cpu {
+        document.insert("cpu".to_string(), json!(cpu.as_ref().ok()));
+    }
+    if let Some(memory) = &info.memory {
+        document.insert("memory".to_string(), json!(memory.as_ref().ok()));
+    }
+    if let Some(processes) = &info.processes {
+        document.insert("process".to_string(), json!(processes.as_ref().ok()));
+    }
+    if let Some(disks) = &info.disks {
+        document.insert("disk".to_string(), json!(disks.as_ref().ok()));
+    }
+    if let Some(networks) = &info.networks {
+        document.insert("network".to_string(), json!(networks));
+    }
+    if let Some(sensors) = &info.sensors {
+        document.insert("sensors".to_string(), json!(sensors.as_ref().ok()));
+    }
+    print_document(format, &document);
+}
+
+fn print_process_matches(matches: &[ProcessMatch], memory: Option<&HashMap<u32, ProcMemory>>) {
+    println!(
+        "{:>7} {:>7} {:<12} {:>6} {:>9}{}  COMMAND",
+        "PID",
+        "PPID",
+        "USER",
+        "CPU%",
+        "RSS",
+        if memory.is_some() {
+            format!(" {:>9} {:>9}", "PSS", "USS")
+        } else {
+            String::new()
+        }
+    );
+    for process in matches {
+        println!(
+            "{:>7} {:>7} {:<12} {:>6.1} {:>9}{}  {}",
+            process.pid,
+            process
+                .ppid
+                .map_or("-".to_string(), |ppid| ppid.to_string()),
+            process.user.as_deref().unwrap_or("?"),
+            process.cpu_usage,
+            format_bytes(process.memory),
+            memory.map_or(String::new(), |memory| format!(
+                " {}",
+                mem::format_pss_columns(memory, process.pid)
+            )),
+            process.command()
+        );
+    }
+}
+
+fn print_hunt(args: &HuntArgs, format: OutputFormat) {
+    let matches = or_exit(hunt_processes(
+        &args.match_options.to_matcher(&args.pattern),
+    ));
+    if format != OutputFormat::Text {
+        print_document(format, &matches);
+        if matches.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+    if matches.is_empty() {
+        println!("No process matches {:?}", args.pattern);
+        std::process::exit(1);
+    }
+    let memory = args
+        .pss
+        .then(|| mem::read_process_memory(matches.iter().map(|process| process.pid)));
+    print_process_matches(&matches, memory.as_ref());
+}
+
+fn confirm(prompt: &str) -> bool {
+    print!("{} [y/N] ", prompt);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim(), "y" | "Y" | "yes" | "YES")
+}
+
+fn kill(args: &KillArgs) {
+    let signal = match parse_signal(&args.signal) {
+        Some(signal) if is_signal_supported(signal) => signal,
+        Some(_) => {
+            eprintln!("Signal {} is not supported on this platform", args.signal);
+            std::process::exit(2);
+        }
+        None => {
+            eprintln!("Unknown signal: {}", args.signal);
+            std::process::exit(2);
+        }
+    };
+    let targets = match &args.pattern {
+        Some(pattern) => hunt_processes(&args.match_options.to_matcher(pattern)),
+        None => hunt_processes(&ProcessMatcher::from_pids(&args.pids)),
+    };
+    let targets = or_exit(targets);
+    let mut failed = false;
+    for pid in &args.pids {
+        if !targets.iter().any(|target| target.pid == *pid) {
+            println!("{}: {}", pid, KillOutcome::NotFound);
+            failed = true;
+        }
+    }
+    if targets.is_empty() {
+        println!("No process to signal");
+        std::process::exit(1);
+    }
+
+    print_process_matches(&targets, None);
+    if args.dry_run {
+        return;
+    }
+    let prompt = format!(
+        "Send SIG{} to {} process(es)?",
+        signal_name(signal),
+        targets.len()
+    );
+    if !args.yes && !confirm(&prompt) {
+        println!("Aborted");
+        std::process::exit(1);
+    }
+
+    let pids: Vec<u32> = targets.iter().map(|target| target.pid).collect();
+    for report in or_exit(send_signal(&pids, signal)) {
+        println!("{}: {}", report.pid, report.outcome);
+        if report.outcome != KillOutcome::Delivered {
+            failed = true;
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
 }
 
-fn print_process_brief_info() {
-    let (proccess_num, root) = get_processes_brief_info();
-    println!("Process number: {}", proccess_num);
-    println!("Root process: {}", root);
-    println!("");
+fn system_source(proc_root: Option<&Path>) -> Box<dyn SystemSource> {
+    match proc_root {
+        Some(root) => Box::new(ProcfsSource::new(root)),
+        None => Box::new(LiveSource::new()),
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
+    let proc_root = cli.proc_root.as_deref();
+    if proc_root.is_some() && !cli.command.reads_proc_root() {
+        eprintln!("--proc-root only applies to info, tree, mem, record, serve and watch");
+        std::process::exit(2);
+    }
 
     match cli.command {
         Commands::Info(args) => {
-            let mut all = true; // Default value for all
-            match args.battery {
-                true => {
-                    all = false; // Set all to false if battery is true
-                    print_battery_brief_info();
-                }
-                false => {}
-            }
-            match args.cpu {
-                true => {
-                    all = false; // Set all to false if cpu is true
-                    print_cpu_brief_info();
-                }
-                false => {}
-            }
-            match args.memory {
-                true => {
-                    all = false; // Set all to false if memory is true
-                    print_memory_brief_info();
-                }
-                false => {}
-            }
-            match args.process {
-                true => {
-                    all = false; // Set all to false if process is true
-                    print_process_brief_info();
-                }
-                false => {}
-            }
-            if all {
-                print_battery_brief_info();
-                print_cpu_brief_info();
-                print_memory_brief_info();
-                print_process_brief_info();
+            let info = InfoData::collect(&args, system_source(proc_root).as_mut());
+            if cli.format == OutputFormat::Text {
+                print_info(&info, args.detailed);
+            } else {
+                print_info_document(&info, args.detailed, cli.format);
             }
         }
+        Commands::Hunt(args) => print_hunt(&args, cli.format),
+        Commands::Kill(args) => kill(&args),
+        Commands::Hog(args) => hog::run(&args, cli.format),
+        Commands::Io(args) => io::run(&args, cli.format),
+        Commands::Leaks(args) => leaks::run(&args, cli.format),
+        Commands::Mem(args) => mem::run(&args, proc_root, cli.format),
+        Commands::Top(args) => top::run(&args),
+        Commands::Tui(args) => tui::run(&args),
+        Commands::Tree(args) => tree::run(&args, system_source(proc_root).as_mut()),
+        Commands::Battery(args) => battery::run(&args, cli.format),
+        Commands::Record(args) => record::run(&args, system_source(proc_root).as_mut()),
+        Commands::Replay(args) => replay::replay(&args, cli.format),
+        Commands::Query(args) => replay::query(&args, cli.format),
+        Commands::Serve(args) => serve::run(&args, system_source(proc_root).as_mut()),
+        Commands::Watch(args) => watch::run(&args, system_source(proc_root).as_mut()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use proc_hunter::models_fn_packer::{SensorKind, SensorReading};
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(super::parse_duration("1s"), Ok(Duration::from_secs(1)));
+        assert_eq!(
+            super::parse_duration("250ms"),
+            Ok(Duration::from_millis(250))
+        );
+        assert_eq!(super::parse_duration("1.5m"), Ok(Duration::from_secs(90)));
+        assert_eq!(super::parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(super::parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert!(super::parse_duration("1w").is_err());
+        assert!(super::parse_duration("soon").is_err());
+        assert!(super::parse_duration("99999999999999999999999h").is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(
+            super::parse_interval("250ms"),
+            Ok(Duration::from_millis(250))
+        );
+        assert!(super::parse_interval("0").is_err());
+        assert!(super::parse_interval("0ms").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(super::parse_size("512"), Ok(512));
+        assert_eq!(super::parse_size("4GiB"), Ok(4 << 30));
+        assert_eq!(super::parse_size("1.5 KiB"), Ok(1536));
+        assert!(super::parse_size("4GB").is_err());
+        assert!(super::parse_size("big").is_err());
+        assert!(super::parse_size("99999999999TiB").is_err());
+        assert!(super::parse_size(&"9".repeat(400)).is_err());
+    }
+
+    #[test]
+    fn test_format_temperature() {
+        let mut reading = SensorReading {
+            chip: "coretemp".to_string(),
+            device: None,
+            label: "Core 0".to_string(),
+            kind: SensorKind::Cpu,
+            core: Some(0),
+            temperature: 52.0,
+            max: None,
+            critical: None,
+        };
+        assert_eq!(super::format_temperature(&reading), "52.0°C");
+        reading.max = Some(80.0);
+        reading.critical = Some(100.0);
+        assert_eq!(
+            super::format_temperature(&reading),
+            "52.0°C (max 80.0°C, critical 100.0°C)"
+        );
     }
 }
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use clap::Args;
+use proc_hunter::models_fn_packer::{ProcMemory, ProcfsSource, SystemSource};
+use serde::Serialize;
+
+use crate::output::{OutputFormat, print_document};
+use crate::{MatchOptions, format_bytes, or_exit};
+
+#[derive(Args)]
+pub struct MemArgs {
+    /// PID, or pattern selecting the processes to report
+    target: String,
+
+    #[command(flatten)]
+    match_options: MatchOptions,
+}
+
+#[derive(Serialize)]
+struct ProcessMemory {
+    pid: u32,
+    name: String,
+    command: String,
+    #[serde(flatten)]
+    memory: ProcMemory,
+    uss: u64,
+}
+
+pub fn run(args: &MemArgs, proc_root: Option<&Path>, format: OutputFormat) {
+    let mut source = ProcfsSource::new(proc_root.unwrap_or(Path::new("/proc")));
+    let mut rows = or_exit(source.processes());
+    match args.target.parse::<u32>() {
+        Ok(pid) => rows.retain(|row| row.pid == pid),
+        Err(_) => {
+            let matcher = args.match_options.to_matcher(&args.target);
+            rows.retain(|row| row.pid != std::process::id() && matcher.matches_row(row));
+        }
+    }
+    if rows.is_empty() {
+        eprintln!("No process matches {:?}", args.target);
+        std::process::exit(1);
+    }
+
+    let mut processes = Vec::new();
+    let mut failed = false;
+    for row in rows {
+        match source.process_memory(row.pid) {
+            Ok(memory) => processes.push(ProcessMemory {
+                pid: row.pid,
+                name: row.name,
+                command: row.command,
+                uss: memory.uss(),
+                memory,
+            }),
+            Err(err) => {
+                eprintln!("{}: {}", row.pid, err);
+                failed = true;
+            }
+        }
+    }
+
+    if format != OutputFormat::Text {
+        print_document(format, &processes);
+    } else if !processes.is_empty() {
+        print_memory_table(&processes);
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn print_memory_table(processes: &[ProcessMemory]) {
+    println!(
+        "{:>7} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}  COMMAND",
+        "PID", "RSS", "PSS", "USS", "SHARED", "SWAP", "ANON", "FILE"
+    );
+    let mut total = ProcMemory::default();
+    for process in processes {
+        let memory = &process.memory;
+        println!(
+            "{:>7} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}  {}",
+            process.pid,
+            format_bytes(memory.rss),
+            format_bytes(memory.pss),
+            format_bytes(memory.uss()),
+            format_bytes(memory.shared()),
+            format_bytes(memory.swap),
+            format_bytes(memory.anonymous),
+            format_bytes(memory.file_backed()),
+            process.command
+        );
+        total.rss += memory.rss;
+        total.pss += memory.pss;
+        total.private_clean += memory.private_clean;
+        total.private_dirty += memory.private_dirty;
+        total.swap_pss += memory.swap_pss;
+    }
+    if processes.len() > 1 {
+        // Summing RSS or swap counts shared pages once per process; the PSS
+        // totals are what the group really costs.
+        println!();
+        println!(
+            "{} processes: PSS {} (RSS sum {}), USS {}, swap PSS {}",
+            processes.len(),
+            format_bytes(total.pss),
+            format_bytes(total.rss),
+            format_bytes(total.uss()),
+            format_bytes(total.swap_pss)
+        );
+    }
+}
+
+/// Memory breakdown of the given live processes, leaving out the ones that
+/// cannot be read, for the optional PSS columns of process listings.
+pub fn read_process_memory(pids: impl IntoIterator<Item = u32>) -> HashMap<u32, ProcMemory> {
+    let source = ProcfsSource::new("/proc");
+    pids.into_iter()
+        .filter_map(|pid| Some((pid, source.process_memory(pid).ok()?)))
+        .collect()
+}
+
+/// PSS and USS columns for `pid`, dashes when unknown.
+pub fn format_pss_columns(memory: &HashMap<u32, ProcMemory>, pid: u32) -> String {
+    match memory.get(&pid) {
+        Some(memory) => format!(
+            "{:>9} {:>9}",
+            format_bytes(memory.pss),
+            format_bytes(memory.uss())
+        ),
+        None => format!("{:>9} {:>9}", "-", "-"),
+    }
+}
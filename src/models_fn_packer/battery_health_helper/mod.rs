@@ -0,0 +1,11 @@
+mod packer;
+
+pub use packer::BatteryHealth;
+pub use packer::CapacityProjection;
+pub use packer::HealthSample;
+pub use packer::append_health_history;
+pub use packer::battery_id;
+pub(crate) use packer::fit_line;
+pub use packer::format_date;
+pub use packer::project_capacity;
+pub use packer::read_health_history;
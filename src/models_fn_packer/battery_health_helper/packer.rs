@@ -0,0 +1,378 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::models_fn_packer::battery_helper::BatteryDetails;
+use crate::{Error, Result};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+const HISTORY_HEADER: &str = "# date\tbattery\tenergy_full_j\tenergy_full_design_j\tcycle_count";
+
+/// Current wear of a single pack.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatteryHealth {
+    pub index: usize,
+    /// Identifier used in the history file, see [`battery_id`].
+    pub battery: String,
+    pub model: Option<String>,
+    /// Energy stored when fully charged, in joules.
+    pub energy_full: f32,
+    /// Energy the pack was designed to hold when new, in joules.
+    pub energy_full_design: f32,
+    /// `energy_full` relative to `energy_full_design`, between 0.0 and 1.0.
+    pub state_of_health: f32,
+    /// Capacity lost since new, `1.0 - state_of_health`.
+    pub wear_level: f32,
+    pub cycle_count: Option<u32>,
+}
+
+impl From<&BatteryDetails> for BatteryHealth {
+    fn from(battery: &BatteryDetails) -> Self {
+        BatteryHealth {
+            index: battery.index,
+            battery: battery_id(battery),
+            model: battery.model.clone(),
+            energy_full: battery.energy_full,
+            energy_full_design: battery.energy_full_design,
+            state_of_health: battery.state_of_health,
+            wear_level: 1.0 - battery.state_of_health,
+            cycle_count: battery.cycle_count,
+        }
+    }
+}
+
+/// One line of the health history file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HealthSample {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub battery: String,
+    /// In joules.
+    pub energy_full: f32,
+    /// In joules.
+    pub energy_full_design: f32,
+    pub cycle_count: Option<u32>,
+}
+
+impl HealthSample {
+    pub fn new(health: &BatteryHealth, timestamp: u64) -> Self {
+        HealthSample {
+            timestamp,
+            battery: health.battery.clone(),
+            energy_full: health.energy_full,
+            energy_full_design: health.energy_full_design,
+            cycle_count: health.cycle_count,
+        }
+    }
+
+    /// Full capacity relative to the design capacity, `None` without a
+    /// design capacity.
+    pub fn state_of_health(&self) -> Option<f32> {
+        if self.energy_full_design > 0.0 {
+            Some(self.energy_full / self.energy_full_design)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where the state of health of a pack is heading.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapacityProjection {
+    pub battery: String,
+    /// Number of history samples the trend is fitted to.
+    pub samples: usize,
+    /// State of health according to the trend at the last sample.
+    pub state_of_health: f32,
+    /// Fraction of the design capacity lost per year. Negative when the
+    /// capacity appears to grow, which happens after calibration.
+    pub loss_per_year: f32,
+    pub threshold: f32,
+    /// When the trend crosses `threshold`, in seconds since the Unix epoch.
+    /// `None` when the capacity is not shrinking. Can lie in the past.
+    pub crosses_at: Option<u64>,
+}
+
+/// Stable name of a pack: its serial number, or its index when the
+/// backend does not report one.
+pub fn battery_id(battery: &BatteryDetails) -> String {
+    match battery.serial_number.as_deref().map(str::trim) {
+        Some(serial) if !serial.is_empty() => serial.replace(char::is_whitespace, "_"),
+        _ => format!("battery{}", battery.index),
+    }
+}
+
+/// Appends `samples` to the history file at `path`, creating the file and
+/// its parent directories if needed.
+pub fn append_health_history(path: &Path, samples: &[HealthSample]) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut buffer = String::new();
+    if file.metadata()?.len() == 0 {
+        buffer.push_str(HISTORY_HEADER);
+        buffer.push('\n');
+    }
+    for sample in samples {
+        buffer.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            format_timestamp(sample.timestamp),
+            sample.battery,
+            sample.energy_full,
+            sample.energy_full_design,
+            sample
+                .cycle_count
+                .map_or("-".to_string(), |count| count.to_string()),
+        ));
+    }
+    file.write_all(buffer.as_bytes())?;
+    Ok(())
+}
+
+/// Reads a history file written by [`append_health_history`]. A missing
+/// file is an empty history.
+pub fn read_health_history(path: &Path) -> Result<Vec<HealthSample>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut samples = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let sample = parse_history_line(line).ok_or_else(|| {
+            Error::Parse(format!("{}:{}: invalid line", path.display(), number + 1))
+        })?;
+        samples.push(sample);
+    }
+    Ok(samples)
+}
+
+fn parse_history_line(line: &str) -> Option<HealthSample> {
+    let mut fields = line.split('\t');
+    let timestamp = parse_timestamp(fields.next()?)?;
+    let battery = fields.next()?.to_string();
+    let energy_full = fields.next()?.parse().ok()?;
+    let energy_full_design = fields.next()?.parse().ok()?;
+    let cycle_count = match fields.next()? {
+        "-" => None,
+        count => Some(count.parse().ok()?),
+    };
+    Some(HealthSample {
+        timestamp,
+        battery,
+        energy_full,
+        energy_full_design,
+        cycle_count,
+    })
+}
+
+/// Fits a least-squares line through the state of health of every pack in
+/// `history` and projects when it falls below `threshold` (e.g. 0.8).
+/// Packs with fewer than two distinct sample times are left out.
+pub fn project_capacity(history: &[HealthSample], threshold: f32) -> Vec<CapacityProjection> {
+    let mut batteries: Vec<&str> = Vec::new();
+    for sample in history {
+        if !batteries.contains(&sample.battery.as_str()) {
+            batteries.push(&sample.battery);
+        }
+    }
+    let mut projections = Vec::new();
+    for battery in batteries {
+        let points: Vec<(f64, f64)> = history
+            .iter()
+            .filter(|sample| sample.battery == battery)
+            .filter_map(|sample| {
+                let health = sample.state_of_health()?;
+                Some((sample.timestamp as f64, health as f64))
+            })
+            .collect();
+        let Some((slope, intercept)) = fit_line(&points) else {
+            continue;
+        };
+        let last = points.iter().map(|(x, _)| *x).fold(f64::MIN, f64::max);
+        let crosses_at = (slope < 0.0)
+            .then(|| (threshold as f64 - intercept) / slope)
+            .map(|x| x.max(0.0) as u64);
+        projections.push(CapacityProjection {
+            battery: battery.to_string(),
+            samples: points.len(),
+            state_of_health: (slope * last + intercept) as f32,
+            loss_per_year: (-slope * 365.25 * SECONDS_PER_DAY as f64) as f32,
+            threshold,
+            crosses_at,
+        });
+    }
+    projections
+}
+
+// Least-squares fit of y = slope * x + intercept. `None` when all x are equal.
+pub(crate) fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return None;
+    }
+    // Centre x to keep the precision with epoch-sized values.
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let mut sxx = 0.0;
+    let mut sxy = 0.0;
+    for (x, y) in points {
+        sxx += (x - mean_x) * (x - mean_x);
+        sxy += (x - mean_x) * (y - mean_y);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD`, in UTC.
+pub fn format_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days((timestamp / SECONDS_PER_DAY) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// RFC 3339 in UTC, e.g. `2024-05-01T08:30:00Z`.
+fn format_timestamp(timestamp: u64) -> String {
+    let seconds = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(timestamp),
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn parse_timestamp(text: &str) -> Option<u64> {
+    let (date, time) = text.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second)
+}
+
+// Howard Hinnant's days_from_civil / civil_from_days.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HealthSample;
+
+    fn sample(battery: &str, timestamp: u64, energy_full: f32) -> HealthSample {
+        HealthSample {
+            timestamp,
+            battery: battery.to_string(),
+            energy_full,
+            energy_full_design: 100.0,
+            cycle_count: Some(10),
+        }
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        assert_eq!(super::format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(super::format_timestamp(951_827_696), "2000-02-29T12:34:56Z");
+        assert_eq!(super::format_date(1_792_195_200), "2026-10-17");
+        for timestamp in [0, 951_827_696, 1_792_195_200, 4_102_444_799] {
+            let text = super::format_timestamp(timestamp);
+            assert_eq!(super::parse_timestamp(&text), Some(timestamp));
+        }
+        assert_eq!(super::parse_timestamp("2024-13-01T00:00:00Z"), None);
+        assert_eq!(super::parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("proc_hunter_health_{}.tsv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let first = sample("SN1", 1_700_000_000, 95.5);
+        let mut second = sample("battery1", 1_700_086_400, 80.0);
+        second.cycle_count = None;
+
+        super::append_health_history(&path, std::slice::from_ref(&first)).unwrap();
+        super::append_health_history(&path, std::slice::from_ref(&second)).unwrap();
+        let history = super::read_health_history(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(history, vec![first, second]);
+        assert_eq!(content.matches("# date").count(), 1);
+    }
+
+    #[test]
+    fn test_read_missing_history() {
+        let path = std::path::Path::new("/nonexistent/proc_hunter/history.tsv");
+        assert!(super::read_health_history(path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_project_capacity() {
+        let year = 31_557_600;
+        let history = vec![
+            sample("A", 0, 100.0),
+            sample("B", 0, 90.0),
+            sample("A", year, 95.0),
+            sample("A", 2 * year, 90.0),
+            sample("B", year, 90.0),
+        ];
+        let projections = super::project_capacity(&history, 0.8);
+
+        assert_eq!(projections.len(), 2);
+        let a = &projections[0];
+        assert_eq!(a.battery, "A");
+        assert_eq!(a.samples, 3);
+        assert!((a.loss_per_year - 0.05).abs() < 1e-4);
+        assert!((a.state_of_health - 0.9).abs() < 1e-4);
+        let crosses_at = a.crosses_at.unwrap() as f64;
+        assert!((crosses_at - 4.0 * year as f64).abs() < 60.0);
+
+        let b = &projections[1];
+        assert_eq!(b.loss_per_year, 0.0);
+        assert_eq!(b.crosses_at, None);
+    }
+
+    #[test]
+    fn test_project_capacity_needs_two_times() {
+        let history = vec![sample("A", 5, 100.0), sample("A", 5, 90.0)];
+        assert!(super::project_capacity(&history, 0.8).is_empty());
+    }
+}
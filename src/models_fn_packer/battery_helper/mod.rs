@@ -1,3 +1,14 @@
 mod packer;
 
+pub use packer::BatteryDetails;
+pub use packer::BatteryInfo;
+pub use packer::BatteryRateSmoother;
+pub use packer::BatteryTimeEstimates;
+pub use packer::DEFAULT_RATE_SMOOTHING;
+pub use packer::TimeEstimate;
+pub use packer::get_batteries;
+#[allow(deprecated)]
 pub use packer::get_battery_brief_info;
+pub use packer::get_battery_info;
+pub use packer::sample_battery_time;
+pub use packer::sample_battery_time_with;
@@ -1,32 +1,407 @@
-pub fn get_battery_brief_info() -> Option<(f32, f32)> {
-    let mut sum_energy = 0.0;
-    let mut sum_energy_full = 0.0;
-    let manager = battery::Manager::new();
-    let manager = match manager {
-        Ok(manager) => manager,
-        Err(_) => return None,
-    };
-    let batteries_r = manager.batteries();
-    let batteries = match batteries_r {
-        Ok(batteries) => batteries,
-        Err(_) => return None,
-    };
-    for battery_r in batteries.into_iter().collect::<Vec<_>>() {
-        let battery = match battery_r {
-            Ok(battery) => battery,
-            Err(_) => return None,
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// Energy figures summed over every battery of the machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatteryInfo {
+    pub battery_count: usize,
+    /// Energy currently stored, in joules.
+    pub energy: f32,
+    /// Energy stored when fully charged, in joules.
+    pub energy_full: f32,
+    /// Combined charge or discharge rate, in watts.
+    pub energy_rate: f32,
+}
+
+impl BatteryInfo {
+    /// Sums the figures of `batteries`.
+    pub fn from_batteries(batteries: &[BatteryDetails]) -> Self {
+        BatteryInfo {
+            battery_count: batteries.len(),
+            energy: batteries.iter().map(|battery| battery.energy).sum(),
+            energy_full: batteries.iter().map(|battery| battery.energy_full).sum(),
+            energy_rate: batteries.iter().map(|battery| battery.energy_rate).sum(),
+        }
+    }
+
+    /// Charge level between 0.0 and 1.0, `None` without any capacity.
+    pub fn state_of_charge(&self) -> Option<f32> {
+        if self.energy_full > 0.0 {
+            Some(self.energy / self.energy_full)
+        } else {
+            None
+        }
+    }
+}
+
+/// Everything the backend reports about a single battery.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatteryDetails {
+    /// Position in the backend's enumeration order, starting at 0.
+    pub index: usize,
+    pub vendor: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    /// Cell chemistry, e.g. `lithium-ion`.
+    pub technology: String,
+    /// One of `charging`, `discharging`, `full`, `empty` or `unknown`.
+    pub state: String,
+    pub cycle_count: Option<u32>,
+    /// Current voltage, in volts.
+    pub voltage: f32,
+    /// Energy currently stored, in joules.
+    pub energy: f32,
+    /// Energy stored when fully charged, in joules.
+    pub energy_full: f32,
+    /// Energy the pack was designed to hold when new, in joules.
+    pub energy_full_design: f32,
+    /// Charge or discharge rate, in watts.
+    pub energy_rate: f32,
+    /// Charge level between 0.0 and 1.0.
+    pub state_of_charge: f32,
+    /// `energy_full` relative to `energy_full_design`, between 0.0 and 1.0.
+    pub state_of_health: f32,
+}
+
+impl From<(usize, battery::Battery)> for BatteryDetails {
+    fn from((index, battery): (usize, battery::Battery)) -> Self {
+        BatteryDetails {
+            index,
+            vendor: battery.vendor().map(str::to_string),
+            model: battery.model().map(str::to_string),
+            serial_number: battery.serial_number().map(str::to_string),
+            technology: battery.technology().to_string(),
+            state: battery.state().to_string(),
+            cycle_count: battery.cycle_count(),
+            voltage: battery.voltage().value,
+            energy: battery.energy().value,
+            energy_full: battery.energy_full().value,
+            energy_full_design: battery.energy_full_design().value,
+            energy_rate: battery.energy_rate().value,
+            state_of_charge: battery.state_of_charge().value,
+            state_of_health: battery.state_of_health().value,
+        }
+    }
+}
+
+/// Reads every battery of the machine, in the backend's order.
+///
+/// Batteries that fail to report are skipped; an error is only returned
+/// when none of them could be read, or when there are none at all
+/// ([`Error::NoBatteries`]).
+pub fn get_batteries() -> Result<Vec<BatteryDetails>> {
+    let manager = battery::Manager::new()?;
+    let mut batteries = Vec::new();
+    let mut last_error = None;
+    for (index, battery_r) in manager.batteries()?.enumerate() {
+        match battery_r {
+            Ok(battery) => batteries.push(BatteryDetails::from((index, battery))),
+            Err(err) => last_error = Some(err),
+        }
+    }
+    if !batteries.is_empty() {
+        return Ok(batteries);
+    }
+    match last_error {
+        Some(err) => Err(err.into()),
+        None => Err(Error::NoBatteries),
+    }
+}
+
+/// Sums the energy figures of every battery that can be read, with the
+/// same error behaviour as [`get_batteries`].
+pub fn get_battery_info() -> Result<BatteryInfo> {
+    Ok(BatteryInfo::from_batteries(&get_batteries()?))
+}
+
+/// Smoothing factor used by [`sample_battery_time`].
+pub const DEFAULT_RATE_SMOOTHING: f32 = 0.3;
+
+/// Estimated time until a battery, or the whole pack, is empty or full.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeEstimate {
+    /// Battery index, `None` for the combined pack.
+    pub index: Option<usize>,
+    /// Charging state the estimate is based on.
+    pub state: String,
+    /// Exponentially smoothed charge or discharge rate, in watts.
+    pub smoothed_rate: f32,
+    /// Seconds until empty, only set while discharging.
+    pub time_to_empty: Option<u64>,
+    /// Seconds until full, only set while charging.
+    pub time_to_full: Option<u64>,
+}
+
+/// Time estimates for every battery and for the combined pack.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatteryTimeEstimates {
+    pub batteries: Vec<TimeEstimate>,
+    pub combined: TimeEstimate,
+}
+
+/// Smooths the rate reported by each battery over repeated samples.
+///
+/// A single `energy_rate()` reading jumps around with the load, so each new
+/// reading only moves the smoothed rate by `alpha` of the difference. The
+/// history of a battery is dropped when its state changes, e.g. when the
+/// charger gets plugged in.
+#[derive(Debug, Clone)]
+pub struct BatteryRateSmoother {
+    alpha: f32,
+    // (state, smoothed rate in watts), indexed like the batteries.
+    rates: Vec<Option<(String, f32)>>,
+}
+
+impl BatteryRateSmoother {
+    /// `alpha` is the weight of each new reading and is clamped to
+    /// `0.01..=1.0`; 1.0 disables smoothing.
+    pub fn new(alpha: f32) -> Self {
+        BatteryRateSmoother {
+            alpha: alpha.clamp(0.01, 1.0),
+            rates: Vec::new(),
+        }
+    }
+
+    /// Feeds one reading of every battery into the smoothed rates.
+    pub fn update(&mut self, batteries: &[BatteryDetails]) {
+        for battery in batteries {
+            if self.rates.len() <= battery.index {
+                self.rates.resize(battery.index + 1, None);
+            }
+            let slot = &mut self.rates[battery.index];
+            *slot = match slot.take() {
+                Some((state, rate)) if state == battery.state => {
+                    Some((state, rate + self.alpha * (battery.energy_rate - rate)))
+                }
+                _ => Some((battery.state.clone(), battery.energy_rate)),
+            };
+        }
+    }
+
+    /// Smoothed rate of the battery at `index`, in watts.
+    pub fn smoothed_rate(&self, index: usize) -> Option<f32> {
+        self.rates
+            .get(index)
+            .and_then(|slot| slot.as_ref())
+            .map(|(_, rate)| *rate)
+    }
+
+    /// Estimates the remaining time of `batteries` from their current
+    /// energy and the smoothed rates. Batteries never passed to
+    /// [`BatteryRateSmoother::update`] use their instantaneous rate.
+    pub fn estimate(&self, batteries: &[BatteryDetails]) -> BatteryTimeEstimates {
+        let mut estimates = Vec::new();
+        // Positive while the pack as a whole is draining.
+        let mut net_drain = 0.0;
+        let mut energy = 0.0;
+        let mut energy_full = 0.0;
+        for battery in batteries {
+            let rate = self
+                .smoothed_rate(battery.index)
+                .unwrap_or(battery.energy_rate);
+            match battery.state.as_str() {
+                "discharging" => net_drain += rate,
+                "charging" => net_drain -= rate,
+                _ => {}
+            }
+            energy += battery.energy;
+            energy_full += battery.energy_full;
+            estimates.push(time_estimate(
+                Some(battery.index),
+                &battery.state,
+                rate,
+                battery.energy,
+                battery.energy_full,
+            ));
+        }
+        let state = if net_drain > 0.0 {
+            "discharging"
+        } else if net_drain < 0.0 {
+            "charging"
+        } else if batteries.iter().all(|battery| battery.state == "full") {
+            "full"
+        } else {
+            "unknown"
         };
-        let energy = battery.energy().value;
-        let energy_full = battery.energy_full().value;
-        sum_energy += energy;
-        sum_energy_full += energy_full;
+        BatteryTimeEstimates {
+            batteries: estimates,
+            combined: time_estimate(None, state, net_drain.abs(), energy, energy_full),
+        }
+    }
+}
+
+fn time_estimate(
+    index: Option<usize>,
+    state: &str,
+    rate: f32,
+    energy: f32,
+    energy_full: f32,
+) -> TimeEstimate {
+    let seconds = |joules: f32| (rate > 0.0).then(|| (joules.max(0.0) / rate).round() as u64);
+    TimeEstimate {
+        index,
+        state: state.to_string(),
+        smoothed_rate: rate,
+        time_to_empty: if state == "discharging" {
+            seconds(energy)
+        } else {
+            None
+        },
+        time_to_full: if state == "charging" {
+            seconds(energy_full - energy)
+        } else {
+            None
+        },
+    }
+}
+
+/// Reads the batteries `samples` times spread over `window` and estimates
+/// their remaining time from the smoothed rates.
+pub fn sample_battery_time(window: Duration, samples: usize) -> Result<BatteryTimeEstimates> {
+    sample_battery_time_with(get_batteries, window, samples).map(|(_, estimates)| estimates)
+}
+
+/// Like [`sample_battery_time`], reading the batteries through `read`
+/// instead of the battery backend. Also returns the last reading.
+pub fn sample_battery_time_with<F>(
+    mut read: F,
+    window: Duration,
+    samples: usize,
+) -> Result<(Vec<BatteryDetails>, BatteryTimeEstimates)>
+where
+    F: FnMut() -> Result<Vec<BatteryDetails>>,
+{
+    let samples = samples.max(1);
+    let mut smoother = BatteryRateSmoother::new(DEFAULT_RATE_SMOOTHING);
+    let mut batteries = read()?;
+    smoother.update(&batteries);
+    for _ in 1..samples {
+        std::thread::sleep(window / (samples - 1) as u32);
+        batteries = read()?;
+        smoother.update(&batteries);
+    }
+    let estimates = smoother.estimate(&batteries);
+    Ok((batteries, estimates))
+}
+
+/// Returns `None` when the battery backend fails, and zeroes on machines
+/// without a battery.
+#[deprecated(note = "use get_battery_info() instead")]
+pub fn get_battery_brief_info() -> Option<(f32, f32)> {
+    match get_battery_info() {
+        Ok(info) => Some((info.energy, info.energy_full)),
+        Err(Error::NoBatteries) => Some((0.0, 0.0)),
+        Err(_) => None,
     }
-    return Some((sum_energy, sum_energy_full));
 }
 
 #[cfg(test)]
 mod tests {
 
+    #[test]
+    fn test_battery_info_state_of_charge() {
+        let info = super::BatteryInfo {
+            battery_count: 1,
+            energy: 90.0,
+            energy_full: 180.0,
+            energy_rate: 0.0,
+        };
+        assert_eq!(info.state_of_charge(), Some(0.5));
+
+        let info = super::BatteryInfo {
+            battery_count: 0,
+            energy: 0.0,
+            energy_full: 0.0,
+            energy_rate: 0.0,
+        };
+        assert_eq!(info.state_of_charge(), None);
+    }
+
+    fn battery(index: usize, state: &str, energy: f32, energy_rate: f32) -> super::BatteryDetails {
+        super::BatteryDetails {
+            index,
+            vendor: None,
+            model: None,
+            serial_number: None,
+            technology: "lithium-ion".to_string(),
+            state: state.to_string(),
+            cycle_count: None,
+            voltage: 12.0,
+            energy,
+            energy_full: 180_000.0,
+            energy_full_design: 200_000.0,
+            energy_rate,
+            state_of_charge: energy / 180_000.0,
+            state_of_health: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_rate_smoothing() {
+        let mut smoother = super::BatteryRateSmoother::new(0.5);
+        smoother.update(&[battery(0, "discharging", 90_000.0, 10.0)]);
+        assert_eq!(smoother.smoothed_rate(0), Some(10.0));
+        smoother.update(&[battery(0, "discharging", 90_000.0, 20.0)]);
+        assert_eq!(smoother.smoothed_rate(0), Some(15.0));
+
+        // Plugging in the charger starts over.
+        smoother.update(&[battery(0, "charging", 90_000.0, 30.0)]);
+        assert_eq!(smoother.smoothed_rate(0), Some(30.0));
+        assert_eq!(smoother.smoothed_rate(1), None);
+    }
+
+    #[test]
+    fn test_time_estimate() {
+        let mut smoother = super::BatteryRateSmoother::new(1.0);
+        let batteries = [
+            battery(0, "discharging", 90_000.0, 20.0),
+            battery(1, "charging", 170_000.0, 5.0),
+        ];
+        smoother.update(&batteries);
+        let estimates = smoother.estimate(&batteries);
+
+        assert_eq!(estimates.batteries[0].time_to_empty, Some(4500));
+        assert_eq!(estimates.batteries[0].time_to_full, None);
+        assert_eq!(estimates.batteries[1].time_to_empty, None);
+        assert_eq!(estimates.batteries[1].time_to_full, Some(2000));
+
+        // The pack drains 15 W net, 260 kJ are left.
+        assert_eq!(estimates.combined.index, None);
+        assert_eq!(estimates.combined.state, "discharging");
+        assert_eq!(estimates.combined.smoothed_rate, 15.0);
+        assert_eq!(estimates.combined.time_to_empty, Some(17_333));
+    }
+
+    #[test]
+    fn test_time_estimate_without_rate() {
+        let smoother = super::BatteryRateSmoother::new(0.3);
+        let estimates = smoother.estimate(&[battery(0, "full", 180_000.0, 0.0)]);
+
+        assert_eq!(estimates.combined.state, "full");
+        assert_eq!(estimates.combined.time_to_empty, None);
+        assert_eq!(estimates.combined.time_to_full, None);
+    }
+
+    #[test]
+    fn test_get_batteries() {
+        match (super::get_batteries(), super::get_battery_info()) {
+            (Ok(batteries), Ok(info)) => {
+                assert_eq!(batteries.len(), info.battery_count);
+                for (index, battery) in batteries.iter().enumerate() {
+                    assert_eq!(battery.index, index);
+                    assert!((0.0..=1.0).contains(&battery.state_of_charge));
+                }
+            }
+            (Err(_), Err(_)) => {}
+            _ => panic!("get_batteries and get_battery_info should agree"),
+        }
+    }
+
     #[test]
     fn test_serial_number() {
         let manager = battery::Manager::new().unwrap();
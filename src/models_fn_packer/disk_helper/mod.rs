@@ -0,0 +1,6 @@
+mod packer;
+
+pub use packer::DiskInfo;
+pub use packer::PSEUDO_FILE_SYSTEMS;
+pub use packer::get_disk_info;
+pub use packer::is_pseudo_file_system;
@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use sysinfo::Disks;
+use systemstat::{Platform, System};
+
+use crate::Result;
+
+/// File system types that do not store anything on a device.
+pub const PSEUDO_FILE_SYSTEMS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rpc_pipefs",
+    "securityfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+/// A mounted file system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiskInfo {
+    /// What is mounted, e.g. `/dev/nvme0n1p2`.
+    pub device: String,
+    pub file_system: String,
+    pub mount_point: String,
+    /// Sizes in bytes. Available space is what unprivileged users can still
+    /// write, which can be less than total minus used.
+    pub total_space: u64,
+    pub used_space: u64,
+    pub available_space: u64,
+    /// Zero for file systems without a fixed inode table, such as btrfs.
+    pub total_inodes: u64,
+    pub used_inodes: u64,
+    pub available_inodes: u64,
+    pub is_removable: bool,
+    pub is_read_only: bool,
+    /// Whether the file system is virtual, see [`is_pseudo_file_system`].
+    pub is_pseudo: bool,
+}
+
+impl DiskInfo {
+    /// Used space in percent of the space usable by unprivileged users, the
+    /// way `df` computes it.
+    pub fn usage(&self) -> f64 {
+        let usable = self.used_space + self.available_space;
+        if usable == 0 {
+            0.0
+        } else {
+            self.used_space as f64 / usable as f64 * 100.0
+        }
+    }
+
+    /// Used inodes in percent, `None` without an inode table.
+    pub fn inode_usage(&self) -> Option<f64> {
+        (self.total_inodes > 0).then(|| self.used_inodes as f64 / self.total_inodes as f64 * 100.0)
+    }
+}
+
+/// Whether a mount of type `file_system` and `total_space` bytes is virtual:
+/// one of [`PSEUDO_FILE_SYSTEMS`], or without any space at all.
+pub fn is_pseudo_file_system(file_system: &str, total_space: u64) -> bool {
+    PSEUDO_FILE_SYSTEMS.contains(&file_system) || total_space == 0
+}
+
+/// Mounted file systems in mount order. Pseudo file systems are left out
+/// unless `include_pseudo` is set.
+pub fn get_disk_info(include_pseudo: bool) -> Result<Vec<DiskInfo>> {
+    let mounts = System::new().mounts()?;
+    let disks = Disks::new_with_refreshed_list();
+    let mut infos = Vec::with_capacity(mounts.len());
+    for mount in mounts {
+        let total_space = mount.total.as_u64();
+        let is_pseudo = is_pseudo_file_system(&mount.fs_type, total_space);
+        if is_pseudo && !include_pseudo {
+            continue;
+        }
+        let disk = disks
+            .list()
+            .iter()
+            .find(|disk| disk.mount_point() == Path::new(&mount.fs_mounted_on));
+        infos.push(DiskInfo {
+            is_removable: disk.is_some_and(|disk| disk.is_removable()),
+            is_read_only: is_read_only(&mount.fs_mounted_on)
+                .unwrap_or_else(|| disk.is_some_and(|disk| disk.is_read_only())),
+            device: mount.fs_mounted_from,
+            file_system: mount.fs_type,
+            mount_point: mount.fs_mounted_on,
+            total_space,
+            used_space: total_space.saturating_sub(mount.free.as_u64()),
+            available_space: mount.avail.as_u64(),
+            total_inodes: mount.files_total as u64,
+            used_inodes: mount.files as u64,
+            available_inodes: mount.files_avail as u64,
+            is_pseudo,
+        });
+    }
+    Ok(infos)
+}
+
+// sysinfo only knows the read-only flag of the file systems it lists, which
+// leaves out the pseudo ones.
+#[cfg(unix)]
+fn is_read_only(mount_point: &str) -> Option<bool> {
+    let path = std::ffi::CString::new(mount_point).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is only read after
+    // statvfs reported that it filled it.
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+    Some(stat.f_flag & libc::ST_RDONLY != 0)
+}
+
+#[cfg(not(unix))]
+fn is_read_only(_mount_point: &str) -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiskInfo, get_disk_info, is_pseudo_file_system};
+
+    #[test]
+    fn test_is_pseudo_file_system() {
+        assert!(is_pseudo_file_system("proc", 0));
+        assert!(is_pseudo_file_system("tmpfs", 1 << 30));
+        assert!(is_pseudo_file_system("ext4", 0));
+        assert!(!is_pseudo_file_system("ext4", 1 << 30));
+        assert!(!is_pseudo_file_system("vfat", 1 << 20));
+    }
+
+    #[test]
+    fn test_usage() {
+        let disk = DiskInfo {
+            device: "/dev/sda1".to_string(),
+            file_system: "ext4".to_string(),
+            mount_point: "/".to_string(),
+            total_space: 1000,
+            used_space: 600,
+            available_space: 200,
+            total_inodes: 0,
+            used_inodes: 0,
+            available_inodes: 0,
+            is_removable: false,
+            is_read_only: false,
+            is_pseudo: false,
+        };
+        assert!((disk.usage() - 75.0).abs() < 1e-9);
+        assert_eq!(disk.inode_usage(), None);
+    }
+
+    #[test]
+    fn test_get_disk_info() {
+        let disks = get_disk_info(false).unwrap();
+        assert!(disks.iter().all(|disk| !disk.is_pseudo));
+
+        let all = get_disk_info(true).unwrap();
+        assert!(all.len() >= disks.len());
+        if cfg!(target_os = "linux") {
+            assert!(all.iter().any(|disk| disk.file_system == "proc"));
+        }
+    }
+}
@@ -0,0 +1,10 @@
+mod packer;
+
+pub use packer::DEFAULT_ALLOWLIST;
+pub use packer::HogKind;
+pub use packer::HogPolicy;
+pub use packer::IoDelta;
+pub use packer::ProcessUsage;
+pub use packer::UsageTracker;
+pub use packer::is_same_process;
+pub use packer::sample_hogs;
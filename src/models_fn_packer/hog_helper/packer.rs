@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use sysinfo::{Pid, ProcessesToUpdate};
+
+use crate::Result;
+use crate::models_fn_packer::hunt_helper::{MatchField, MatchMode, ProcessMatcher};
+use crate::models_fn_packer::sys_info_helper::get_system;
+use crate::models_fn_packer::top_helper::{ProcessRow, ProcessTable};
+use crate::models_fn_packer::tree_helper::ProcessTree;
+
+/// Process names that are never throttled, whatever the limits say.
+pub const DEFAULT_ALLOWLIST: [&str; 14] = [
+    "systemd*",
+    "init",
+    "launchd",
+    "kthreadd",
+    "sshd",
+    "dbus-daemon",
+    "dbus-broker*",
+    "Xorg",
+    "Xwayland",
+    "gnome-shell",
+    "kwin_*",
+    "login",
+    "agetty",
+    "containerd*",
+];
+
+/// What a process is ranked by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HogKind {
+    /// Mean CPU usage over the window.
+    Cpu,
+    /// Resident set size growth over the window.
+    Memory,
+    /// Bytes read and written over the window.
+    Io,
+}
+
+/// Bytes a process read from and wrote to disk since the previous sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoDelta {
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+}
+
+/// Process reported by [`UsageTracker::report`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    pub user: Option<String>,
+    pub command: String,
+    /// Mean CPU usage over the window, in percent.
+    pub cpu_usage: f32,
+    /// Resident set size at the end of the window, in bytes.
+    pub memory: u64,
+    /// Change of the resident set size over the window, in bytes.
+    pub memory_growth: i64,
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+    /// Parent, grandparent and so on, as `(pid, name)`.
+    pub parents: Vec<(u32, String)>,
+    /// Start time in seconds since the Unix epoch, `None` if unknown. Tells
+    /// the process apart from a later one reusing its PID.
+    pub start_time: Option<u64>,
+}
+
+impl ProcessUsage {
+    /// Value the process is ranked by for `kind`.
+    pub fn score(&self, kind: HogKind) -> f64 {
+        match kind {
+            HogKind::Cpu => self.cpu_usage as f64,
+            HogKind::Memory => self.memory_growth as f64,
+            HogKind::Io => (self.read_bytes + self.written_bytes) as f64,
+        }
+    }
+}
+
+struct Entry {
+    last: ProcessRow,
+    first_memory: u64,
+    cpu_sum: f64,
+    samples: u32,
+    io: IoDelta,
+}
+
+/// Accumulates successive process samples into per-process totals.
+#[derive(Default)]
+pub struct UsageTracker {
+    entries: HashMap<u32, Entry>,
+    latest: Vec<u32>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds one sample. A PID that comes back under another name is taken
+    /// as a new process.
+    pub fn add(&mut self, sample: &[(ProcessRow, IoDelta)]) {
+        self.latest.clear();
+        for (row, io) in sample {
+            self.latest.push(row.pid);
+            let entry = self.entries.entry(row.pid).or_insert_with(|| Entry {
+                last: row.clone(),
+                first_memory: row.memory,
+                cpu_sum: 0.0,
+                samples: 0,
+                io: IoDelta::default(),
+            });
+            if entry.last.name != row.name {
+                *entry = Entry {
+                    last: row.clone(),
+                    first_memory: row.memory,
+                    cpu_sum: 0.0,
+                    samples: 0,
+                    io: IoDelta::default(),
+                };
+            }
+            entry.last = row.clone();
+            entry.cpu_sum += row.cpu_usage as f64;
+            entry.samples += 1;
+            entry.io.read_bytes += io.read_bytes;
+            entry.io.written_bytes += io.written_bytes;
+        }
+    }
+
+    /// Processes of the latest sample, worst offenders first.
+    pub fn report(&self, kind: HogKind) -> Vec<ProcessUsage> {
+        let rows: Vec<ProcessRow> = self
+            .latest
+            .iter()
+            .map(|pid| self.entries[pid].last.clone())
+            .collect();
+        let tree = ProcessTree::new(rows);
+        let mut report: Vec<ProcessUsage> = self
+            .latest
+            .iter()
+            .map(|pid| {
+                let entry = &self.entries[pid];
+                ProcessUsage {
+                    pid: *pid,
+                    name: entry.last.name.clone(),
+                    user: entry.last.user.clone(),
+                    command: entry.last.command.clone(),
+                    cpu_usage: (entry.cpu_sum / entry.samples as f64) as f32,
+                    memory: entry.last.memory,
+                    memory_growth: entry.last.memory as i64 - entry.first_memory as i64,
+                    read_bytes: entry.io.read_bytes,
+                    written_bytes: entry.io.written_bytes,
+                    parents: tree
+                        .ancestors(*pid)
+                        .map(|parent| (parent.pid, parent.name.clone()))
+                        .collect(),
+                    start_time: None,
+                }
+            })
+            .collect();
+        report.sort_by(|a, b| {
+            b.score(kind)
+                .total_cmp(&a.score(kind))
+                .then(a.pid.cmp(&b.pid))
+        });
+        report
+    }
+}
+
+/// Samples every process `samples` times over `window` and returns them
+/// worst offenders first. Processes that exit during the window and the
+/// current process are left out.
+pub fn sample_hogs(window: Duration, samples: u32, kind: HogKind) -> Result<Vec<ProcessUsage>> {
+    let samples = samples.max(1);
+    let interval = (window / samples).max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    let mut table = ProcessTable::new();
+    // CPU usage and disk counters are relative to the previous refresh.
+    table.sample(None)?;
+    let mut tracker = UsageTracker::new();
+    for _ in 0..samples {
+        std::thread::sleep(interval);
+        let rows = table.sample(None)?;
+        let system = get_system()?;
+        let sample: Vec<(ProcessRow, IoDelta)> = rows
+            .into_iter()
+            .filter(|row| row.pid != std::process::id())
+            .map(|row| {
+                let io = system
+                    .process(Pid::from_u32(row.pid))
+                    .map(|process| {
+                        let usage = process.disk_usage();
+                        IoDelta {
+                            read_bytes: usage.read_bytes,
+                            written_bytes: usage.written_bytes,
+                        }
+                    })
+                    .unwrap_or_default();
+                (row, io)
+            })
+            .collect();
+        tracker.add(&sample);
+    }
+    let mut report = tracker.report(kind);
+    let system = get_system()?;
+    for usage in &mut report {
+        usage.start_time = system
+            .process(Pid::from_u32(usage.pid))
+            .map(|process| process.start_time());
+    }
+    Ok(report)
+}
+
+/// Whether the process `usage` describes still runs under its PID, with the
+/// same name and, when known, the same start time. Check this before acting
+/// on a report that may be stale, e.g. after asking for confirmation.
+pub fn is_same_process(usage: &ProcessUsage) -> Result<bool> {
+    let mut system = get_system()?;
+    let pid = Pid::from_u32(usage.pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    Ok(system.process(pid).is_some_and(|process| {
+        process.name().to_string_lossy() == usage.name
+            && usage
+                .start_time
+                .is_none_or(|start_time| start_time == process.start_time())
+    }))
+}
+
+/// Limits above which a process is an offender, and the processes that are
+/// never touched no matter what.
+#[derive(Debug, Clone)]
+pub struct HogPolicy {
+    /// Mean CPU usage, in percent.
+    pub cpu_limit: Option<f32>,
+    /// Resident set size growth over the window, in bytes.
+    pub memory_growth_limit: Option<u64>,
+    /// Bytes read and written over the window.
+    pub io_limit: Option<u64>,
+    /// Protect every process owned by root.
+    pub protect_root: bool,
+    allowlist: Vec<ProcessMatcher>,
+}
+
+impl Default for HogPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HogPolicy {
+    /// A policy without limits that protects root processes and the names in
+    /// [`DEFAULT_ALLOWLIST`].
+    pub fn new() -> Self {
+        let allowlist = DEFAULT_ALLOWLIST
+            .iter()
+            .map(|pattern| {
+                ProcessMatcher::new(pattern, MatchMode::Glob, false)
+                    .expect("default allowlist patterns are valid globs")
+                    .with_fields(&[MatchField::Name])
+            })
+            .collect();
+        HogPolicy {
+            cpu_limit: None,
+            memory_growth_limit: None,
+            io_limit: None,
+            protect_root: true,
+            allowlist,
+        }
+    }
+
+    /// Adds processes to the allowlist.
+    pub fn allow(mut self, matcher: ProcessMatcher) -> Self {
+        self.allowlist.push(matcher);
+        self
+    }
+
+    /// Whether `usage` goes over any of the configured limits.
+    pub fn exceeds(&self, usage: &ProcessUsage) -> bool {
+        self.cpu_limit.is_some_and(|limit| usage.cpu_usage > limit)
+            || self
+                .memory_growth_limit
+                .is_some_and(|limit| usage.memory_growth > limit as i64)
+            || self
+                .io_limit
+                .is_some_and(|limit| usage.read_bytes + usage.written_bytes > limit)
+    }
+
+    /// Whether `usage` must be left alone: init, kernel threads, this process,
+    /// root processes unless allowed, and allowlisted names.
+    pub fn is_protected(&self, usage: &ProcessUsage) -> bool {
+        let own_pid = std::process::id();
+        if usage.pid <= 1 || usage.pid == own_pid {
+            return true;
+        }
+        // Kernel threads have no command line.
+        if usage.command == format!("[{}]", usage.name) {
+            return true;
+        }
+        if self.protect_root && usage.user.as_deref() == Some("root") {
+            return true;
+        }
+        let row = ProcessRow {
+            pid: usage.pid,
+            ppid: usage.parents.first().map(|(pid, _)| *pid),
+            user: usage.user.clone(),
+            cpu_usage: usage.cpu_usage,
+            memory: usage.memory,
+            state: String::new(),
+            threads: 1,
+            name: usage.name.clone(),
+            command: usage.command.clone(),
+        };
+        self.allowlist
+            .iter()
+            .any(|matcher| matcher.matches_row(&row))
+    }
+
+    /// Processes of `report` over a limit and not protected.
+    pub fn offenders<'a>(&self, report: &'a [ProcessUsage]) -> Vec<&'a ProcessUsage> {
+        report
+            .iter()
+            .filter(|usage| self.exceeds(usage) && !self.is_protected(usage))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HogKind, HogPolicy, IoDelta, ProcessUsage, UsageTracker, is_same_process};
+    use crate::models_fn_packer::hunt_helper::{MatchMode, ProcessMatcher};
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    fn row(pid: u32, ppid: u32, name: &str, cpu_usage: f32, memory: u64) -> ProcessRow {
+        ProcessRow {
+            ppid: Some(ppid),
+            user: Some("alice".to_string()),
+            cpu_usage,
+            memory,
+            state: "Running".to_string(),
+            ..ProcessRow::fixture(pid, name)
+        }
+    }
+
+    fn io(read_bytes: u64) -> IoDelta {
+        IoDelta {
+            read_bytes,
+            written_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_usage_tracker() {
+        let mut tracker = UsageTracker::new();
+        tracker.add(&[
+            (row(10, 1, "shell", 0.0, 100), io(0)),
+            (row(20, 10, "spin", 100.0, 100), io(0)),
+            (row(30, 10, "leak", 0.0, 100), io(500)),
+        ]);
+        tracker.add(&[
+            (row(10, 1, "shell", 0.0, 100), io(0)),
+            (row(20, 10, "spin", 50.0, 100), io(0)),
+            (row(30, 10, "leak", 10.0, 1100), io(500)),
+        ]);
+
+        let report = tracker.report(HogKind::Cpu);
+        assert_eq!(report[0].pid, 20);
+        assert_eq!(report[0].cpu_usage, 75.0);
+        assert_eq!(report[0].parents, vec![(10, "shell".to_string())]);
+
+        let report = tracker.report(HogKind::Memory);
+        assert_eq!(report[0].pid, 30);
+        assert_eq!(report[0].memory_growth, 1000);
+
+        let report = tracker.report(HogKind::Io);
+        assert_eq!(report[0].pid, 30);
+        assert_eq!(report[0].read_bytes, 1000);
+
+        // Exited processes drop out of the report.
+        tracker.add(&[(row(10, 1, "shell", 0.0, 100), io(0))]);
+        assert_eq!(tracker.report(HogKind::Cpu).len(), 1);
+    }
+
+    #[test]
+    fn test_policy() {
+        let usage = |pid: u32, name: &str, user: &str, cpu_usage: f32| ProcessUsage {
+            pid,
+            name: name.to_string(),
+            user: Some(user.to_string()),
+            command: name.to_string(),
+            cpu_usage,
+            memory: 0,
+            memory_growth: 0,
+            read_bytes: 0,
+            written_bytes: 0,
+            parents: Vec::new(),
+            start_time: None,
+        };
+        let mut policy = HogPolicy::new();
+        policy.cpu_limit = Some(80.0);
+        let policy = policy.allow(ProcessMatcher::new("ffmpeg", MatchMode::Exact, false).unwrap());
+        let report = vec![
+            usage(100, "spin", "alice", 99.0),
+            usage(101, "idle", "alice", 1.0),
+            usage(102, "sshd", "alice", 99.0),
+            usage(103, "spin", "root", 99.0),
+            usage(104, "ffmpeg", "alice", 99.0),
+            usage(1, "init", "alice", 99.0),
+        ];
+
+        let offenders: Vec<u32> = policy.offenders(&report).iter().map(|u| u.pid).collect();
+        assert_eq!(offenders, vec![100]);
+
+        let mut policy = policy;
+        policy.protect_root = false;
+        let offenders: Vec<u32> = policy.offenders(&report).iter().map(|u| u.pid).collect();
+        assert_eq!(offenders, vec![100, 103]);
+    }
+
+    #[test]
+    fn test_is_same_process() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("Failed to spawn sleep process");
+        let pid = child.id();
+        let start_time = {
+            let mut system = crate::models_fn_packer::sys_info_helper::get_system().unwrap();
+            let sysinfo_pid = sysinfo::Pid::from_u32(pid);
+            system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[sysinfo_pid]), true);
+            system.process(sysinfo_pid).unwrap().start_time()
+        };
+        let usage = ProcessUsage {
+            pid,
+            name: "sleep".to_string(),
+            user: None,
+            command: "sleep 30".to_string(),
+            cpu_usage: 0.0,
+            memory: 0,
+            memory_growth: 0,
+            read_bytes: 0,
+            written_bytes: 0,
+            parents: Vec::new(),
+            start_time: Some(start_time),
+        };
+
+        let same = is_same_process(&usage).unwrap();
+        let restarted = is_same_process(&ProcessUsage {
+            start_time: Some(start_time - 1),
+            ..usage.clone()
+        })
+        .unwrap();
+        let renamed = is_same_process(&ProcessUsage {
+            name: "other".to_string(),
+            ..usage.clone()
+        })
+        .unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert!(same);
+        assert!(!restarted);
+        assert!(!renamed);
+        assert!(!is_same_process(&usage).unwrap());
+    }
+}
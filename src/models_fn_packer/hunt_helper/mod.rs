@@ -0,0 +1,7 @@
+mod packer;
+
+pub use packer::MatchField;
+pub use packer::MatchMode;
+pub use packer::ProcessMatch;
+pub use packer::ProcessMatcher;
+pub use packer::hunt_processes;
@@ -0,0 +1,400 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use regex::{Regex, RegexBuilder};
+use sysinfo::{Process, ThreadKind, Users};
+
+use crate::Result;
+use crate::models_fn_packer::sys_info_helper::refresh_processes_with_details;
+use crate::models_fn_packer::top_helper::ProcessRow;
+
+/// How a hunt pattern is compared against process fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The whole field must be equal to the pattern.
+    Exact,
+    /// Shell-style wildcards: `*` matches any run of characters, `?` a single one.
+    Glob,
+    /// Regular expression, matching anywhere in the field.
+    Regex,
+}
+
+/// Process field a pattern can be matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchField {
+    /// Process name, as reported by the kernel.
+    Name,
+    /// Full command line, arguments joined by single spaces.
+    Cmd,
+    /// Path of the executable.
+    Exe,
+}
+
+impl MatchField {
+    pub const ALL: [MatchField; 3] = [MatchField::Name, MatchField::Cmd, MatchField::Exe];
+}
+
+/// Compiled pattern used to select processes.
+#[derive(Debug, Clone)]
+pub struct ProcessMatcher {
+    selection: Selection,
+}
+
+#[derive(Debug, Clone)]
+enum Selection {
+    Pattern {
+        regex: Regex,
+        fields: Vec<MatchField>,
+    },
+    Pids(HashSet<u32>),
+}
+
+impl ProcessMatcher {
+    /// Compiles `pattern` according to `mode`. The matcher looks at every
+    /// [`MatchField`] until narrowed down with [`ProcessMatcher::with_fields`].
+    pub fn new(pattern: &str, mode: MatchMode, ignore_case: bool) -> Result<Self> {
+        let source = match mode {
+            MatchMode::Exact => format!("^{}$", regex::escape(pattern)),
+            MatchMode::Glob => glob_to_regex(pattern),
+            MatchMode::Regex => pattern.to_string(),
+        };
+        let regex = RegexBuilder::new(&source)
+            .case_insensitive(ignore_case)
+            .build()?;
+        Ok(ProcessMatcher {
+            selection: Selection::Pattern {
+                regex,
+                fields: MatchField::ALL.to_vec(),
+            },
+        })
+    }
+
+    /// Builds a matcher selecting exactly the given process IDs.
+    pub fn from_pids(pids: &[u32]) -> Self {
+        ProcessMatcher {
+            selection: Selection::Pids(pids.iter().copied().collect()),
+        }
+    }
+
+    /// Restricts matching to the given fields. An empty list keeps all of
+    /// them. Has no effect on a matcher built with
+    /// [`ProcessMatcher::from_pids`].
+    pub fn with_fields(mut self, fields: &[MatchField]) -> Self {
+        if let Selection::Pattern {
+            fields: selected, ..
+        } = &mut self.selection
+            && !fields.is_empty()
+        {
+            *selected = fields.to_vec();
+        }
+        self
+    }
+
+    /// Returns the first field of `process` that matches, if any. Always
+    /// `None` for a matcher built with [`ProcessMatcher::from_pids`], which
+    /// looks at no field.
+    pub fn match_field(&self, process: &Process) -> Option<MatchField> {
+        let Selection::Pattern { regex, fields } = &self.selection else {
+            return None;
+        };
+        fields.iter().copied().find(|field| match field {
+            MatchField::Name => regex.is_match(&process.name().to_string_lossy()),
+            MatchField::Cmd => {
+                !process.cmd().is_empty() && regex.is_match(&join_cmd(process.cmd()))
+            }
+            MatchField::Exe => process
+                .exe()
+                .is_some_and(|exe| regex.is_match(&exe.to_string_lossy())),
+        })
+    }
+
+    pub fn matches(&self, process: &Process) -> bool {
+        self.selects_pid(process.pid().as_u32()) || self.match_field(process).is_some()
+    }
+
+    /// Like [`ProcessMatcher::matches`] for a row of a process listing, e.g.
+    /// from a recorded snapshot. Rows carry no executable path, so
+    /// [`MatchField::Exe`] never matches.
+    pub fn matches_row(&self, row: &ProcessRow) -> bool {
+        match &self.selection {
+            Selection::Pids(pids) => pids.contains(&row.pid),
+            Selection::Pattern { regex, fields } => fields.iter().any(|field| match field {
+                MatchField::Name => regex.is_match(&row.name),
+                MatchField::Cmd => {
+                    row.command != format!("[{}]", row.name) && regex.is_match(&row.command)
+                }
+                MatchField::Exe => false,
+            }),
+        }
+    }
+
+    fn selects_pid(&self, pid: u32) -> bool {
+        matches!(&self.selection, Selection::Pids(pids) if pids.contains(&pid))
+    }
+}
+
+/// A process selected by [`hunt_processes`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessMatch {
+    pub pid: u32,
+    /// Parent process ID, `None` for root processes.
+    pub ppid: Option<u32>,
+    /// Name of the owning user, `None` if it cannot be resolved.
+    pub user: Option<String>,
+    /// CPU usage in percent since the previous refresh. Can exceed 100 on
+    /// multi-core machines.
+    pub cpu_usage: f32,
+    /// Resident set size in bytes.
+    pub memory: u64,
+    pub name: String,
+    /// Command line arguments, empty for kernel threads.
+    pub cmd: Vec<String>,
+    pub exe: Option<PathBuf>,
+    /// Field the pattern matched on, `None` for processes selected by PID.
+    pub matched_on: Option<MatchField>,
+}
+
+impl ProcessMatch {
+    /// Command line for display, falling back to `[name]` like `ps` does for
+    /// processes without arguments.
+    pub fn command(&self) -> String {
+        if self.cmd.is_empty() {
+            format!("[{}]", self.name)
+        } else {
+            self.cmd.join(" ")
+        }
+    }
+}
+
+/// Returns every process matching `matcher`, ordered by PID. Threads and the
+/// current process are never reported.
+pub fn hunt_processes(matcher: &ProcessMatcher) -> Result<Vec<ProcessMatch>> {
+    let system = refresh_processes_with_details()?;
+    let users = Users::new_with_refreshed_list();
+    let own_pid = std::process::id();
+    let mut matches = Vec::new();
+    for process in system.processes().values() {
+        if process.thread_kind() == Some(ThreadKind::Userland) {
+            continue;
+        }
+        if process.pid().as_u32() == own_pid {
+            continue;
+        }
+        let matched_on = matcher.match_field(process);
+        if matched_on.is_none() && !matcher.selects_pid(process.pid().as_u32()) {
+            continue;
+        }
+        let user = process
+            .user_id()
+            .and_then(|uid| users.get_user_by_id(uid))
+            .map(|user| user.name().to_string());
+        matches.push(ProcessMatch {
+            pid: process.pid().as_u32(),
+            ppid: process.parent().map(|parent| parent.as_u32()),
+            user,
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            name: process.name().to_string_lossy().into_owned(),
+            cmd: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            exe: process.exe().map(|exe| exe.to_path_buf()),
+            matched_on,
+        });
+    }
+    matches.sort_by_key(|process_match| process_match.pid);
+    Ok(matches)
+}
+
+fn join_cmd(cmd: &[std::ffi::OsString]) -> String {
+    cmd.iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+    let mut source = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => source.push_str(".*"),
+            '?' => source.push('.'),
+            _ => source.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    source.push('$');
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchField, MatchMode, ProcessMatcher, Selection};
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    fn is_match(matcher: &ProcessMatcher, text: &str) -> bool {
+        match &matcher.selection {
+            Selection::Pattern { regex, .. } => regex.is_match(text),
+            Selection::Pids(_) => false,
+        }
+    }
+
+    fn fields(matcher: &ProcessMatcher) -> Vec<MatchField> {
+        match &matcher.selection {
+            Selection::Pattern { fields, .. } => fields.clone(),
+            Selection::Pids(_) => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let matcher = ProcessMatcher::new("sleep", MatchMode::Exact, false).unwrap();
+
+        assert!(is_match(&matcher, "sleep"));
+        assert!(!is_match(&matcher, "sleeper"));
+        assert!(!is_match(&matcher, "Sleep"));
+    }
+
+    #[test]
+    fn test_exact_match_escapes_pattern() {
+        let matcher = ProcessMatcher::new("a.b", MatchMode::Exact, false).unwrap();
+
+        assert!(is_match(&matcher, "a.b"));
+        assert!(!is_match(&matcher, "axb"));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        let matcher = ProcessMatcher::new("python3.*", MatchMode::Glob, false).unwrap();
+
+        assert!(is_match(&matcher, "python3.12"));
+        assert!(!is_match(&matcher, "python3"));
+        assert!(!is_match(&matcher, "/usr/bin/python3.12"));
+
+        let matcher = ProcessMatcher::new("kworker/?:*", MatchMode::Glob, false).unwrap();
+        assert!(is_match(&matcher, "kworker/0:1H"));
+    }
+
+    #[test]
+    fn test_regex_match() {
+        let matcher = ProcessMatcher::new(r"--port[= ]\d+", MatchMode::Regex, false).unwrap();
+
+        assert!(is_match(&matcher, "server --port 8080 --verbose"));
+        assert!(!is_match(&matcher, "server --port=http"));
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        assert!(ProcessMatcher::new("(", MatchMode::Regex, false).is_err());
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let matcher = ProcessMatcher::new("Firefox", MatchMode::Exact, true).unwrap();
+
+        assert!(is_match(&matcher, "firefox"));
+    }
+
+    #[test]
+    fn test_with_fields() {
+        let matcher = ProcessMatcher::new("sleep", MatchMode::Exact, false).unwrap();
+        assert_eq!(fields(&matcher), MatchField::ALL.to_vec());
+
+        let matcher = matcher.with_fields(&[MatchField::Exe]);
+        assert_eq!(fields(&matcher), vec![MatchField::Exe]);
+
+        let matcher = matcher.with_fields(&[]);
+        assert_eq!(fields(&matcher), vec![MatchField::Exe]);
+    }
+
+    #[test]
+    fn test_matches_row() {
+        let row = ProcessRow {
+            ppid: Some(1),
+            command: "java -jar app.jar".to_string(),
+            ..ProcessRow::fixture(42, "java")
+        };
+        let matcher = ProcessMatcher::new("java", MatchMode::Exact, false).unwrap();
+        assert!(matcher.matches_row(&row));
+        let matcher = matcher.with_fields(&[MatchField::Cmd]);
+        assert!(!matcher.matches_row(&row));
+        let matcher = ProcessMatcher::new("*app.jar", MatchMode::Glob, false)
+            .unwrap()
+            .with_fields(&[MatchField::Cmd]);
+        assert!(matcher.matches_row(&row));
+        assert!(ProcessMatcher::from_pids(&[42]).matches_row(&row));
+
+        let kernel_thread = ProcessRow {
+            command: "[kthreadd]".to_string(),
+            name: "kthreadd".to_string(),
+            ..row
+        };
+        let matcher = ProcessMatcher::new("[kthreadd]", MatchMode::Exact, false).unwrap();
+        assert!(!matcher.matches_row(&kernel_thread));
+    }
+
+    #[test]
+    fn test_from_pids() {
+        let row = ProcessRow {
+            command: "142".to_string(),
+            ..ProcessRow::fixture(42, "42")
+        };
+        let matcher = ProcessMatcher::from_pids(&[1, 42]);
+        assert!(matcher.matches_row(&row));
+        assert!(!ProcessMatcher::from_pids(&[4, 142]).matches_row(&row));
+        assert!(!ProcessMatcher::from_pids(&[]).matches_row(&row));
+
+        // Narrowing down fields neither drops the PIDs nor matches PID-like
+        // names.
+        let matcher = matcher.with_fields(&[MatchField::Name]);
+        assert!(matcher.matches_row(&row));
+        let matcher = ProcessMatcher::new("42", MatchMode::Exact, false)
+            .unwrap()
+            .with_fields(&[MatchField::Cmd]);
+        assert!(!matcher.matches_row(&row));
+    }
+
+    #[test]
+    fn test_hunt_by_pid() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("Failed to spawn sleep process");
+        let pid = child.id();
+
+        let matches = super::hunt_processes(&ProcessMatcher::from_pids(&[pid])).unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pid, pid);
+        assert_eq!(matches[0].matched_on, None);
+    }
+
+    #[test]
+    fn test_hunt_processes() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("Failed to spawn sleep process");
+        let pid = child.id();
+
+        let matcher = ProcessMatcher::new("sleep 30", MatchMode::Exact, false)
+            .unwrap()
+            .with_fields(&[MatchField::Cmd]);
+        let matches = super::hunt_processes(&matcher).unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+
+        let found = matches
+            .iter()
+            .find(|process_match| process_match.pid == pid)
+            .expect("Spawned process should be hunted");
+        assert_eq!(found.matched_on, Some(MatchField::Cmd));
+        assert_eq!(found.ppid, Some(std::process::id()));
+        assert_eq!(found.command(), "sleep 30");
+    }
+}
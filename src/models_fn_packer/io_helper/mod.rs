@@ -0,0 +1,6 @@
+mod packer;
+
+pub use packer::IoSortKey;
+pub use packer::ProcessIoRow;
+pub use packer::sample_process_io;
+pub use packer::sort_io_rows;
@@ -0,0 +1,165 @@
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use sysinfo::Pid;
+
+use crate::Result;
+use crate::models_fn_packer::hunt_helper::ProcessMatcher;
+use crate::models_fn_packer::procfs_helper::{ProcIo, ProcfsSource};
+use crate::models_fn_packer::sys_info_helper::get_system;
+use crate::models_fn_packer::top_helper::ProcessTable;
+
+/// Disk activity of one process over a sampling window.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessIoRow {
+    pub pid: u32,
+    pub user: Option<String>,
+    pub name: String,
+    pub command: String,
+    /// Bytes read from storage per second over the window.
+    pub read_rate: f64,
+    /// Bytes written to storage per second over the window.
+    pub write_rate: f64,
+    /// Bytes read since the process started.
+    pub total_read_bytes: u64,
+    /// Bytes written since the process started.
+    pub total_written_bytes: u64,
+    /// Syscall counts and cancelled writes from `/proc/<pid>/io`, `None`
+    /// where it is not readable or not on Linux.
+    pub io: Option<ProcIo>,
+}
+
+/// Column an I/O listing can be ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoSortKey {
+    Pid,
+    /// Read rate.
+    Read,
+    /// Write rate.
+    Write,
+    /// Read plus write rate.
+    Total,
+    /// Bytes read since start.
+    TotalRead,
+    /// Bytes written since start.
+    TotalWritten,
+}
+
+/// Measures the disk activity of every process matching `filter`, or all of
+/// them without a filter, over `window`. Threads are skipped.
+pub fn sample_process_io(
+    window: Duration,
+    filter: Option<&ProcessMatcher>,
+) -> Result<Vec<ProcessIoRow>> {
+    let mut table = ProcessTable::new();
+    // Disk counters are relative to the previous refresh.
+    table.sample(filter)?;
+    let started = Instant::now();
+    std::thread::sleep(window);
+    let rows = table.sample(filter)?;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let procfs = ProcfsSource::new("/proc");
+    let system = get_system()?;
+    let mut io_rows = Vec::with_capacity(rows.len());
+    for row in rows {
+        let Some(process) = system.process(Pid::from_u32(row.pid)) else {
+            continue;
+        };
+        let usage = process.disk_usage();
+        io_rows.push(ProcessIoRow {
+            pid: row.pid,
+            user: row.user,
+            name: row.name,
+            command: row.command,
+            read_rate: usage.read_bytes as f64 / elapsed,
+            write_rate: usage.written_bytes as f64 / elapsed,
+            total_read_bytes: usage.total_read_bytes,
+            total_written_bytes: usage.total_written_bytes,
+            io: procfs.process(row.pid).ok().and_then(|details| details.io),
+        });
+    }
+    Ok(io_rows)
+}
+
+/// Sorts `rows` by `key`. Ties are broken by PID.
+pub fn sort_io_rows(rows: &mut [ProcessIoRow], key: IoSortKey, descending: bool) {
+    rows.sort_by(|a, b| {
+        let ordering = match key {
+            IoSortKey::Pid => Ordering::Equal,
+            IoSortKey::Read => a.read_rate.total_cmp(&b.read_rate),
+            IoSortKey::Write => a.write_rate.total_cmp(&b.write_rate),
+            IoSortKey::Total => {
+                (a.read_rate + a.write_rate).total_cmp(&(b.read_rate + b.write_rate))
+            }
+            IoSortKey::TotalRead => a.total_read_bytes.cmp(&b.total_read_bytes),
+            IoSortKey::TotalWritten => a.total_written_bytes.cmp(&b.total_written_bytes),
+        }
+        .then(a.pid.cmp(&b.pid));
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{IoSortKey, ProcessIoRow, sort_io_rows};
+    use crate::models_fn_packer::hunt_helper::ProcessMatcher;
+
+    fn row(pid: u32, read_rate: f64, write_rate: f64, total_read_bytes: u64) -> ProcessIoRow {
+        ProcessIoRow {
+            pid,
+            user: None,
+            name: format!("p{}", pid),
+            command: format!("p{}", pid),
+            read_rate,
+            write_rate,
+            total_read_bytes,
+            total_written_bytes: 0,
+            io: None,
+        }
+    }
+
+    fn pids(rows: &[ProcessIoRow]) -> Vec<u32> {
+        rows.iter().map(|row| row.pid).collect()
+    }
+
+    #[test]
+    fn test_sort_io_rows() {
+        let mut rows = vec![
+            row(1, 10.0, 0.0, 500),
+            row(2, 0.0, 50.0, 100),
+            row(3, 30.0, 30.0, 0),
+        ];
+
+        sort_io_rows(&mut rows, IoSortKey::Read, true);
+        assert_eq!(pids(&rows), vec![3, 1, 2]);
+        sort_io_rows(&mut rows, IoSortKey::Write, true);
+        assert_eq!(pids(&rows), vec![2, 3, 1]);
+        sort_io_rows(&mut rows, IoSortKey::Total, true);
+        assert_eq!(pids(&rows), vec![3, 2, 1]);
+        sort_io_rows(&mut rows, IoSortKey::TotalRead, false);
+        assert_eq!(pids(&rows), vec![3, 2, 1]);
+        sort_io_rows(&mut rows, IoSortKey::Pid, false);
+        assert_eq!(pids(&rows), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sample_process_io() {
+        let own_pid = std::process::id();
+        let matcher = ProcessMatcher::from_pids(&[own_pid]);
+        let rows = super::sample_process_io(Duration::from_millis(50), Some(&matcher)).unwrap();
+
+        assert_eq!(pids(&rows), vec![own_pid]);
+        assert!(rows[0].read_rate >= 0.0);
+        if cfg!(target_os = "linux") {
+            assert!(rows[0].io.is_some());
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod packer;
+
+pub use packer::LeakCriteria;
+pub use packer::LeakTracker;
+pub use packer::MIN_TREND_SAMPLES;
+pub use packer::MemoryTrend;
+pub use packer::sample_memory_trends;
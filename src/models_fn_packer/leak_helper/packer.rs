@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use sysinfo::Pid;
+
+use crate::Result;
+use crate::models_fn_packer::battery_health_helper::fit_line;
+use crate::models_fn_packer::source_helper::now_ms;
+use crate::models_fn_packer::sys_info_helper::{get_memory_info, get_system};
+use crate::models_fn_packer::top_helper::{ProcessRow, ProcessTable};
+
+/// Fewest samples a trend is fitted on.
+pub const MIN_TREND_SAMPLES: usize = 3;
+
+/// Memory of one process over time, fitted with a least-squares line.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryTrend {
+    pub pid: u32,
+    pub name: String,
+    pub command: String,
+    pub samples: usize,
+    /// Resident set size at the last sample, in bytes.
+    pub memory: u64,
+    /// Virtual memory size at the last sample, in bytes.
+    pub virtual_memory: u64,
+    /// Resident set size growth, in bytes per second.
+    pub growth_rate: f64,
+    /// Virtual memory growth, in bytes per second.
+    pub virtual_growth_rate: f64,
+    /// Coefficient of determination of the resident set size fit, from 0
+    /// (no linear trend) to 1 (perfectly linear).
+    pub r_squared: f64,
+    /// Whether the resident set size never went down between two samples.
+    pub monotonic: bool,
+    /// Seconds until the process alone would use up the memory that is
+    /// available now, `None` if it does not grow.
+    pub time_to_exhaustion: Option<u64>,
+}
+
+/// Thresholds a [`MemoryTrend`] has to meet to count as a leak.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeakCriteria {
+    /// Minimum resident set size growth, in bytes per second.
+    pub min_growth_rate: f64,
+    /// Minimum coefficient of determination.
+    pub min_r_squared: f64,
+}
+
+impl MemoryTrend {
+    /// Monotonic growth that is fast enough and close enough to a line.
+    pub fn is_leak(&self, criteria: &LeakCriteria) -> bool {
+        self.monotonic
+            && self.growth_rate >= criteria.min_growth_rate
+            && self.growth_rate > 0.0
+            && self.r_squared >= criteria.min_r_squared
+    }
+}
+
+struct Series {
+    name: String,
+    command: String,
+    // (seconds since the first sample, RSS, virtual memory)
+    points: Vec<(f64, u64, u64)>,
+}
+
+/// Collects memory samples per process and fits trends on them.
+#[derive(Default)]
+pub struct LeakTracker {
+    series: HashMap<u32, Series>,
+    latest: Vec<u32>,
+    first_ms: Option<u64>,
+}
+
+impl LeakTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the processes sampled at `timestamp_ms` with their virtual memory
+    /// size. A PID that comes back under another name starts a new series.
+    pub fn add(&mut self, timestamp_ms: u64, sample: &[(ProcessRow, u64)]) {
+        let first_ms = *self.first_ms.get_or_insert(timestamp_ms);
+        let seconds = timestamp_ms.saturating_sub(first_ms) as f64 / 1000.0;
+        self.latest.clear();
+        for (row, virtual_memory) in sample {
+            self.latest.push(row.pid);
+            let series = self.series.entry(row.pid).or_insert_with(|| Series {
+                name: row.name.clone(),
+                command: row.command.clone(),
+                points: Vec::new(),
+            });
+            if series.name != row.name {
+                series.name = row.name.clone();
+                series.points.clear();
+            }
+            series.command = row.command.clone();
+            series.points.push((seconds, row.memory, *virtual_memory));
+        }
+    }
+
+    /// Trends of the processes of the latest sample that have at least
+    /// [`MIN_TREND_SAMPLES`] samples, fastest growing first.
+    /// `available_memory` is used to project the time to exhaustion.
+    pub fn trends(&self, available_memory: u64) -> Vec<MemoryTrend> {
+        let mut trends: Vec<MemoryTrend> = self
+            .latest
+            .iter()
+            .filter_map(|pid| trend(*pid, &self.series[pid], available_memory))
+            .collect();
+        trends.sort_by(|a, b| {
+            b.growth_rate
+                .total_cmp(&a.growth_rate)
+                .then(a.pid.cmp(&b.pid))
+        });
+        trends
+    }
+}
+
+fn trend(pid: u32, series: &Series, available_memory: u64) -> Option<MemoryTrend> {
+    if series.points.len() < MIN_TREND_SAMPLES {
+        return None;
+    }
+    let rss: Vec<(f64, f64)> = series
+        .points
+        .iter()
+        .map(|(x, memory, _)| (*x, *memory as f64))
+        .collect();
+    let virtual_memory: Vec<(f64, f64)> = series
+        .points
+        .iter()
+        .map(|(x, _, memory)| (*x, *memory as f64))
+        .collect();
+    let (growth_rate, intercept) = fit_line(&rss)?;
+    let (virtual_growth_rate, _) = fit_line(&virtual_memory)?;
+    let (_, memory, last_virtual) = *series.points.last()?;
+    Some(MemoryTrend {
+        pid,
+        name: series.name.clone(),
+        command: series.command.clone(),
+        samples: series.points.len(),
+        memory,
+        virtual_memory: last_virtual,
+        growth_rate,
+        virtual_growth_rate,
+        r_squared: r_squared(&rss, growth_rate, intercept),
+        monotonic: series.points.windows(2).all(|pair| pair[1].1 >= pair[0].1),
+        time_to_exhaustion: (growth_rate > 0.0)
+            .then(|| (available_memory as f64 / growth_rate) as u64),
+    })
+}
+
+// A flat series is perfectly described by its fit.
+fn r_squared(points: &[(f64, f64)], slope: f64, intercept: f64) -> f64 {
+    let mean = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    let mut total = 0.0;
+    let mut residual = 0.0;
+    for (x, y) in points {
+        total += (y - mean) * (y - mean);
+        let predicted = slope * x + intercept;
+        residual += (y - predicted) * (y - predicted);
+    }
+    if total == 0.0 {
+        1.0
+    } else {
+        1.0 - residual / total
+    }
+}
+
+/// Samples the memory of every process every `interval` for `duration` and
+/// returns their trends, fastest growing first. `progress` is called after
+/// every sample with the number of samples taken so far.
+pub fn sample_memory_trends(
+    duration: Duration,
+    interval: Duration,
+    mut progress: impl FnMut(usize),
+) -> Result<Vec<MemoryTrend>> {
+    let mut table = ProcessTable::new();
+    let mut tracker = LeakTracker::new();
+    let started = Instant::now();
+    // A duration too long to represent means sampling until interrupted.
+    let deadline = started.checked_add(duration);
+    let mut next_tick = started;
+    let mut taken = 0;
+    loop {
+        let rows = table.sample(None)?;
+        let system = get_system()?;
+        let sample: Vec<(ProcessRow, u64)> = rows
+            .into_iter()
+            .map(|row| {
+                let virtual_memory = system
+                    .process(Pid::from_u32(row.pid))
+                    .map_or(0, |process| process.virtual_memory());
+                (row, virtual_memory)
+            })
+            .collect();
+        drop(system);
+        tracker.add(now_ms(), &sample);
+        taken += 1;
+        progress(taken);
+
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+        // The last sample is taken at the deadline.
+        next_tick = match (next_tick.checked_add(interval), deadline) {
+            (Some(tick), Some(deadline)) => tick.min(deadline),
+            (Some(tick), None) => tick,
+            (None, Some(deadline)) => deadline,
+            (None, None) => break,
+        };
+        std::thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+    }
+    Ok(tracker.trends(get_memory_info()?.available_memory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LeakCriteria, LeakTracker};
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    fn row(pid: u32, memory: u64) -> ProcessRow {
+        ProcessRow {
+            memory,
+            ..ProcessRow::fixture(pid, &format!("p{}", pid))
+        }
+    }
+
+    #[test]
+    fn test_trends() {
+        let mut tracker = LeakTracker::new();
+        let leaking = [1000, 2000, 3000, 4000];
+        let noisy = [1000, 5000, 1000, 5000];
+        for (step, (leak, noise)) in leaking.iter().zip(noisy).enumerate() {
+            let timestamp_ms = 10_000 * step as u64;
+            tracker.add(
+                timestamp_ms,
+                &[
+                    (row(1, *leak), 4 * leak),
+                    (row(2, noise), 0),
+                    (row(3, 500), 0),
+                ],
+            );
+        }
+        let criteria = LeakCriteria {
+            min_growth_rate: 50.0,
+            min_r_squared: 0.9,
+        };
+
+        let trends = tracker.trends(1_000_000);
+        assert_eq!(trends.len(), 3);
+        let leak = &trends[0];
+        assert_eq!(leak.pid, 1);
+        assert!((leak.growth_rate - 100.0).abs() < 1e-9);
+        assert!((leak.virtual_growth_rate - 400.0).abs() < 1e-9);
+        assert!((leak.r_squared - 1.0).abs() < 1e-9);
+        assert!(leak.monotonic);
+        assert_eq!(leak.time_to_exhaustion, Some(10_000));
+        assert!(leak.is_leak(&criteria));
+
+        let noisy = trends.iter().find(|trend| trend.pid == 2).unwrap();
+        assert!(!noisy.monotonic);
+        assert!(!noisy.is_leak(&criteria));
+
+        let flat = trends.iter().find(|trend| trend.pid == 3).unwrap();
+        assert_eq!(flat.growth_rate, 0.0);
+        assert_eq!(flat.time_to_exhaustion, None);
+        assert!(!flat.is_leak(&criteria));
+    }
+
+    #[test]
+    fn test_too_few_samples() {
+        let mut tracker = LeakTracker::new();
+        tracker.add(0, &[(row(1, 1000), 0)]);
+        tracker.add(1000, &[(row(1, 2000), 0)]);
+        assert!(tracker.trends(1_000_000).is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+mod packer;
+
+pub use packer::render_metrics;
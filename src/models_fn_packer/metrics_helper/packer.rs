@@ -0,0 +1,214 @@
+use std::fmt::Write;
+
+use crate::models_fn_packer::source_helper::Snapshot;
+use crate::models_fn_packer::top_helper::ProcessRow;
+
+const BATTERY_STATES: [&str; 5] = ["charging", "discharging", "empty", "full", "unknown"];
+
+/// Renders `snapshot` in the Prometheus text exposition format. `processes`
+/// get per-process series; pass an empty slice to leave them out.
+///
+/// Every value is in base units: percentages stay percentages as reported
+/// by the OS, but frequencies are in hertz and energy in joules.
+pub fn render_metrics(snapshot: &Snapshot, processes: &[ProcessRow]) -> String {
+    let mut out = Metrics::default();
+    let cpu = &snapshot.cpu;
+    out.family("cpu_usage_percent", "gauge", "Usage of all cores combined.");
+    out.sample("cpu_usage_percent", &[], cpu.global_usage as f64);
+    out.family("core_usage_percent", "gauge", "Usage of each logical core.");
+    for core in &cpu.cores {
+        out.sample(
+            "core_usage_percent",
+            &[("core", &core.name)],
+            core.usage as f64,
+        );
+    }
+    out.family(
+        "core_frequency_hertz",
+        "gauge",
+        "Current frequency of each logical core.",
+    );
+    for core in &cpu.cores {
+        let hertz = core.frequency as f64 * 1_000_000.0;
+        out.sample("core_frequency_hertz", &[("core", &core.name)], hertz);
+    }
+
+    let memory = &snapshot.memory;
+    for (name, help, value) in [
+        (
+            "memory_total_bytes",
+            "Installed memory.",
+            memory.total_memory,
+        ),
+        (
+            "memory_free_bytes",
+            "Memory not used for anything.",
+            memory.free_memory,
+        ),
+        (
+            "memory_available_bytes",
+            "Memory available for new allocations.",
+            memory.available_memory,
+        ),
+        ("memory_used_bytes", "Memory in use.", memory.used_memory),
+        ("swap_total_bytes", "Swap space.", memory.total_swap),
+        ("swap_free_bytes", "Unused swap space.", memory.free_swap),
+        ("swap_used_bytes", "Swap space in use.", memory.used_swap),
+    ] {
+        out.family(name, "gauge", help);
+        out.sample(name, &[], value as f64);
+    }
+
+    if !snapshot.batteries.is_empty() {
+        type BatteryValue = fn(&crate::models_fn_packer::battery_helper::BatteryDetails) -> f32;
+        let gauges: [(&str, &str, BatteryValue); 4] = [
+            ("battery_energy_joules", "Energy stored.", |b| b.energy),
+            (
+                "battery_energy_full_joules",
+                "Energy stored when full.",
+                |b| b.energy_full,
+            ),
+            (
+                "battery_energy_rate_watts",
+                "Charge or discharge rate.",
+                |b| b.energy_rate,
+            ),
+            ("battery_state_of_charge_ratio", "Charge level.", |b| {
+                b.state_of_charge
+            }),
+        ];
+        for (name, help, value) in gauges {
+            out.family(name, "gauge", help);
+            for battery in &snapshot.batteries {
+                let index = battery.index.to_string();
+                out.sample(name, &[("battery", &index)], value(battery) as f64);
+            }
+        }
+        out.family(
+            "battery_state",
+            "gauge",
+            "1 for the current charging state of each battery.",
+        );
+        for battery in &snapshot.batteries {
+            let index = battery.index.to_string();
+            for state in BATTERY_STATES {
+                let value = if battery.state == state { 1.0 } else { 0.0 };
+                out.sample(
+                    "battery_state",
+                    &[("battery", &index), ("state", state)],
+                    value,
+                );
+            }
+        }
+    }
+
+    out.family(
+        "processes",
+        "gauge",
+        "Number of processes, threads excluded.",
+    );
+    out.sample("processes", &[], snapshot.processes.len() as f64);
+
+    if !processes.is_empty() {
+        out.family(
+            "process_cpu_usage_percent",
+            "gauge",
+            "CPU usage of each watched process.",
+        );
+        for row in processes {
+            let pid = row.pid.to_string();
+            let labels = [("pid", pid.as_str()), ("name", row.name.as_str())];
+            out.sample("process_cpu_usage_percent", &labels, row.cpu_usage as f64);
+        }
+        out.family(
+            "process_resident_memory_bytes",
+            "gauge",
+            "Resident set size of each watched process.",
+        );
+        for row in processes {
+            let pid = row.pid.to_string();
+            let labels = [("pid", pid.as_str()), ("name", row.name.as_str())];
+            out.sample("process_resident_memory_bytes", &labels, row.memory as f64);
+        }
+    }
+    out.text
+}
+
+#[derive(Default)]
+struct Metrics {
+    text: String,
+}
+
+impl Metrics {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP proc_hunter_{} {}", name, help);
+        let _ = writeln!(self.text, "# TYPE proc_hunter_{} {}", name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let _ = write!(self.text, "proc_hunter_{}", name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {}", value);
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models_fn_packer::source_helper::Snapshot;
+    use crate::models_fn_packer::sys_info_helper::{CoreInfo, CpuInfo, MemoryInfo};
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    #[test]
+    fn test_render_metrics() {
+        let row = ProcessRow {
+            cpu_usage: 12.5,
+            memory: 2048,
+            command: "say".to_string(),
+            ..ProcessRow::fixture(7, "say \"hi\"")
+        };
+        let snapshot = Snapshot {
+            timestamp_ms: 0,
+            cpu: CpuInfo {
+                physical_core_count: Some(1),
+                global_usage: 50.0,
+                cores: vec![CoreInfo {
+                    name: "cpu0".to_string(),
+                    brand: String::new(),
+                    vendor_id: String::new(),
+                    frequency: 2000,
+                    usage: 50.0,
+                }],
+            },
+            memory: MemoryInfo {
+                total_memory: 1024,
+                ..MemoryInfo::default()
+            },
+            processes: vec![row.clone(), row.clone()],
+            batteries: Vec::new(),
+        };
+        let text = super::render_metrics(&snapshot, &[row]);
+
+        assert!(text.contains("# TYPE proc_hunter_cpu_usage_percent gauge\n"));
+        assert!(text.contains("proc_hunter_cpu_usage_percent 50\n"));
+        assert!(text.contains("proc_hunter_core_frequency_hertz{core=\"cpu0\"} 2000000000\n"));
+        assert!(text.contains("proc_hunter_memory_total_bytes 1024\n"));
+        assert!(text.contains("proc_hunter_processes 2\n"));
+        assert!(!text.contains("battery"));
+        assert!(text.contains(
+            "proc_hunter_process_cpu_usage_percent{pid=\"7\",name=\"say \\\"hi\\\"\"} 12.5\n"
+        ));
+    }
+}
@@ -1,15 +1,147 @@
+mod battery_health_helper;
 mod battery_helper;
+mod disk_helper;
+mod hog_helper;
+mod hunt_helper;
+mod io_helper;
+mod leak_helper;
+mod metrics_helper;
+mod network_helper;
+mod procfs_helper;
+#[cfg(feature = "serde")]
+mod record_helper;
+mod sensor_helper;
+mod signal_helper;
+mod source_helper;
 mod sys_info_helper;
+mod top_helper;
+mod tree_helper;
+mod watch_helper;
 
+pub use battery_health_helper::BatteryHealth;
+pub use battery_health_helper::CapacityProjection;
+pub use battery_health_helper::HealthSample;
+pub use battery_health_helper::append_health_history;
+pub use battery_health_helper::battery_id;
+pub use battery_health_helper::format_date;
+pub use battery_health_helper::project_capacity;
+pub use battery_health_helper::read_health_history;
+pub use battery_helper::BatteryDetails;
+pub use battery_helper::BatteryInfo;
+pub use battery_helper::BatteryRateSmoother;
+pub use battery_helper::BatteryTimeEstimates;
+pub use battery_helper::DEFAULT_RATE_SMOOTHING;
+pub use battery_helper::TimeEstimate;
+pub use battery_helper::get_batteries;
+#[allow(deprecated)]
 pub use battery_helper::get_battery_brief_info;
+pub use battery_helper::get_battery_info;
+pub use battery_helper::sample_battery_time;
+pub use battery_helper::sample_battery_time_with;
+pub use disk_helper::DiskInfo;
+pub use disk_helper::PSEUDO_FILE_SYSTEMS;
+pub use disk_helper::get_disk_info;
+pub use disk_helper::is_pseudo_file_system;
+pub use hog_helper::DEFAULT_ALLOWLIST;
+pub use hog_helper::HogKind;
+pub use hog_helper::HogPolicy;
+pub use hog_helper::IoDelta;
+pub use hog_helper::ProcessUsage;
+pub use hog_helper::UsageTracker;
+pub use hog_helper::is_same_process;
+pub use hog_helper::sample_hogs;
+pub use hunt_helper::MatchField;
+pub use hunt_helper::MatchMode;
+pub use hunt_helper::ProcessMatch;
+pub use hunt_helper::ProcessMatcher;
+pub use hunt_helper::hunt_processes;
+pub use io_helper::IoSortKey;
+pub use io_helper::ProcessIoRow;
+pub use io_helper::sample_process_io;
+pub use io_helper::sort_io_rows;
+pub use leak_helper::LeakCriteria;
+pub use leak_helper::LeakTracker;
+pub use leak_helper::MIN_TREND_SAMPLES;
+pub use leak_helper::MemoryTrend;
+pub use leak_helper::sample_memory_trends;
+pub use metrics_helper::render_metrics;
+pub use network_helper::NetworkInfo;
+pub use network_helper::get_network_info;
+pub use network_helper::sample_network_info;
+pub use procfs_helper::ProcIo;
+pub use procfs_helper::ProcMemory;
+pub use procfs_helper::ProcessDetails;
+pub use procfs_helper::ProcfsSource;
+#[cfg(feature = "serde")]
+pub use record_helper::KEYFRAME_INTERVAL;
+#[cfg(feature = "serde")]
+pub use record_helper::LogHeader;
+#[cfg(feature = "serde")]
+pub use record_helper::LogRecord;
+#[cfg(feature = "serde")]
+pub use record_helper::ProcessDelta;
+#[cfg(feature = "serde")]
+pub use record_helper::SnapshotReader;
+#[cfg(feature = "serde")]
+pub use record_helper::SnapshotWriter;
+#[cfg(feature = "serde")]
+pub use record_helper::read_snapshot_log;
+pub use sensor_helper::SensorKind;
+pub use sensor_helper::SensorReading;
+pub use sensor_helper::cpu_core_id;
+pub use sensor_helper::get_sensor_info;
+pub use sensor_helper::read_hwmon;
+pub use signal_helper::KillOutcome;
+pub use signal_helper::KillReport;
+pub use signal_helper::ReniceOutcome;
+pub use signal_helper::ReniceReport;
+pub use signal_helper::is_signal_supported;
+pub use signal_helper::parse_signal;
+pub use signal_helper::renice;
+pub use signal_helper::send_signal;
+pub use signal_helper::signal_name;
+pub use source_helper::FixtureSource;
+pub use source_helper::LiveSource;
+pub use source_helper::Snapshot;
+pub use source_helper::SystemSource;
+pub use source_helper::now_ms;
+pub use sys_info_helper::CoreInfo;
+pub use sys_info_helper::CpuInfo;
+pub use sys_info_helper::MemoryInfo;
+pub use sys_info_helper::ProcessSummary;
+#[allow(deprecated)]
 pub use sys_info_helper::get_cpu_brief_info;
+#[allow(deprecated)]
 pub use sys_info_helper::get_cpu_brief_info_with_1_ms_sleep;
+pub use sys_info_helper::get_cpu_info;
+#[allow(deprecated)]
 pub use sys_info_helper::get_memory_brief_info;
+pub use sys_info_helper::get_memory_info;
+#[allow(deprecated)]
 pub use sys_info_helper::get_processes_brief_info;
+pub use sys_info_helper::get_processes_summary;
+pub use sys_info_helper::sample_cpu;
+pub use sysinfo::Signal;
+pub use top_helper::ProcessRow;
+pub use top_helper::ProcessTable;
+pub use top_helper::SortKey;
+pub use top_helper::sort_process_rows;
+pub use tree_helper::Ancestors;
+pub use tree_helper::ProcessTree;
+pub use tree_helper::SubtreeTotals;
+pub use tree_helper::Walk;
+pub use watch_helper::AlertEvent;
+pub use watch_helper::AlertKind;
+pub use watch_helper::Measurement;
+pub use watch_helper::Metric;
+pub use watch_helper::Rule;
+pub use watch_helper::Threshold;
+pub use watch_helper::Watcher;
 
 #[cfg(test)]
 mod tests {
     #[test]
+    #[allow(deprecated)]
     fn test_get_battery_brief_info() {
         let result = crate::models_fn_packer::battery_helper::get_battery_brief_info();
         assert!(result.is_some(), "Failed to get battery brief info");
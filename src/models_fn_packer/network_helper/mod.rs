@@ -0,0 +1,5 @@
+mod packer;
+
+pub use packer::NetworkInfo;
+pub use packer::get_network_info;
+pub use packer::sample_network_info;
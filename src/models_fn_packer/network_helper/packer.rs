@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use sysinfo::Networks;
+
+/// A network interface with its counters since boot.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetworkInfo {
+    pub name: String,
+    /// `None` for interfaces without a hardware address, such as loopback.
+    pub mac_address: Option<String>,
+    /// Addresses with their prefix length, e.g. `192.168.1.10/24`.
+    pub addresses: Vec<String>,
+    pub mtu: u64,
+    pub received_bytes: u64,
+    pub transmitted_bytes: u64,
+    pub received_packets: u64,
+    pub transmitted_packets: u64,
+    pub receive_errors: u64,
+    pub transmit_errors: u64,
+    /// Bytes received per second over the sampling window, `None` unless
+    /// the interfaces were sampled.
+    pub receive_rate: Option<f64>,
+    /// Bytes transmitted per second over the sampling window.
+    pub transmit_rate: Option<f64>,
+}
+
+/// Every network interface, sorted by name.
+pub fn get_network_info() -> Vec<NetworkInfo> {
+    network_info(&Networks::new_with_refreshed_list(), None)
+}
+
+/// Every network interface, sorted by name, with its throughput measured
+/// over `window`.
+pub fn sample_network_info(window: Duration) -> Vec<NetworkInfo> {
+    let mut networks = Networks::new_with_refreshed_list();
+    let started = Instant::now();
+    std::thread::sleep(window);
+    // Received and transmitted bytes are relative to the previous refresh.
+    networks.refresh(true);
+    network_info(&networks, Some(started.elapsed().as_secs_f64()))
+}
+
+fn network_info(networks: &Networks, elapsed: Option<f64>) -> Vec<NetworkInfo> {
+    let mut infos: Vec<NetworkInfo> = networks
+        .list()
+        .iter()
+        .map(|(name, data)| NetworkInfo {
+            name: name.clone(),
+            mac_address: (!data.mac_address().is_unspecified())
+                .then(|| data.mac_address().to_string()),
+            addresses: data
+                .ip_networks()
+                .iter()
+                .map(|network| network.to_string())
+                .collect(),
+            mtu: data.mtu(),
+            received_bytes: data.total_received(),
+            transmitted_bytes: data.total_transmitted(),
+            received_packets: data.total_packets_received(),
+            transmitted_packets: data.total_packets_transmitted(),
+            receive_errors: data.total_errors_on_received(),
+            transmit_errors: data.total_errors_on_transmitted(),
+            receive_rate: elapsed.map(|seconds| data.received() as f64 / seconds),
+            transmit_rate: elapsed.map(|seconds| data.transmitted() as f64 / seconds),
+        })
+        .collect();
+    for info in &mut infos {
+        info.addresses.sort();
+    }
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+    infos
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn test_get_network_info() {
+        let networks = super::get_network_info();
+        assert!(networks.windows(2).all(|pair| pair[0].name < pair[1].name));
+        assert!(
+            networks
+                .iter()
+                .all(|network| network.receive_rate.is_none())
+        );
+        if cfg!(target_os = "linux")
+            && let Some(loopback) = networks.iter().find(|network| network.name == "lo")
+        {
+            assert_eq!(loopback.mac_address, None);
+        }
+    }
+
+    #[test]
+    fn test_sample_network_info() {
+        let networks = super::sample_network_info(Duration::from_millis(50));
+        for network in &networks {
+            assert!(network.receive_rate.is_some_and(|rate| rate >= 0.0));
+            assert!(network.transmit_rate.is_some_and(|rate| rate >= 0.0));
+        }
+    }
+}
@@ -0,0 +1,6 @@
+mod packer;
+
+pub use packer::ProcIo;
+pub use packer::ProcMemory;
+pub use packer::ProcessDetails;
+pub use packer::ProcfsSource;
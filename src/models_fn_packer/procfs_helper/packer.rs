@@ -0,0 +1,775 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use sysinfo::{Uid, Users};
+
+use crate::models_fn_packer::battery_helper::{BatteryDetails, get_batteries};
+use crate::models_fn_packer::source_helper::SystemSource;
+use crate::models_fn_packer::sys_info_helper::{CoreInfo, CpuInfo, MemoryInfo};
+use crate::models_fn_packer::top_helper::ProcessRow;
+use crate::{Error, Result};
+
+// USER_HZ, the unit of the times in /proc. Fixed at 100 on every Linux ABI.
+const CLOCK_TICKS_PER_SECOND: f64 = 100.0;
+
+/// Counters read from `/proc/<pid>/io`, all in bytes or calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcIo {
+    /// Bytes passed to read(2) and similar, page cache hits included.
+    pub rchar: u64,
+    /// Bytes passed to write(2) and similar.
+    pub wchar: u64,
+    pub syscr: u64,
+    pub syscw: u64,
+    /// Bytes actually fetched from storage.
+    pub read_bytes: u64,
+    /// Bytes actually sent to storage.
+    pub write_bytes: u64,
+    pub cancelled_write_bytes: u64,
+}
+
+/// Memory of a process from `/proc/<pid>/smaps_rollup`, all in bytes.
+///
+/// Unlike the resident set size, the proportional set size splits every
+/// shared page evenly between the processes mapping it, so the PSS of a pool
+/// of forked workers adds up to what the pool really costs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcMemory {
+    /// Resident set size.
+    pub rss: u64,
+    /// Proportional set size.
+    pub pss: u64,
+    pub shared_clean: u64,
+    pub shared_dirty: u64,
+    pub private_clean: u64,
+    pub private_dirty: u64,
+    /// Resident anonymous memory: heap, stacks and private mappings.
+    pub anonymous: u64,
+    /// Swapped out anonymous memory.
+    pub swap: u64,
+    /// Swap shared out proportionally, like the PSS.
+    pub swap_pss: u64,
+}
+
+impl ProcMemory {
+    /// Unique set size: memory only this process maps, freed if it exits.
+    pub fn uss(&self) -> u64 {
+        self.private_clean + self.private_dirty
+    }
+
+    /// Resident memory backed by files, page cache and shared memory included.
+    pub fn file_backed(&self) -> u64 {
+        self.rss.saturating_sub(self.anonymous)
+    }
+
+    pub fn shared(&self) -> u64 {
+        self.shared_clean + self.shared_dirty
+    }
+}
+
+/// One process as described by `/proc/<pid>/{stat,status,cmdline,io}`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessDetails {
+    pub pid: u32,
+    /// Parent process ID, `None` for PID 1 and kernel roots.
+    pub ppid: Option<u32>,
+    /// `comm` of the process, at most 15 characters.
+    pub name: String,
+    /// Single-letter state from `stat`, e.g. `S` or `R`.
+    pub state: char,
+    pub uid: Option<u32>,
+    /// Command line arguments, empty for kernel threads and zombies.
+    pub cmdline: Vec<String>,
+    pub nice: i64,
+    pub threads: usize,
+    /// Page faults served without I/O.
+    pub minor_faults: u64,
+    /// Page faults that had to read from storage.
+    pub major_faults: u64,
+    pub voluntary_ctxt_switches: Option<u64>,
+    pub nonvoluntary_ctxt_switches: Option<u64>,
+    /// User plus system CPU time, in clock ticks of 1/100 s.
+    pub cpu_ticks: u64,
+    /// Start time after boot, in clock ticks of 1/100 s.
+    pub start_ticks: u64,
+    /// Resident set size in bytes.
+    pub rss: u64,
+    /// `None` when `io` is not readable, usually for other users' processes.
+    pub io: Option<ProcIo>,
+}
+
+impl ProcessDetails {
+    /// Scheduler state spelled the way `sysinfo` does, e.g. `Sleeping`.
+    pub fn state_name(&self) -> &'static str {
+        match self.state {
+            'R' => "Runnable",
+            'S' => "Sleeping",
+            'D' => "UninterruptibleDiskSleep",
+            'Z' => "Zombie",
+            'T' => "Stopped",
+            't' => "Tracing",
+            'X' | 'x' => "Dead",
+            'K' => "Wakekill",
+            'W' => "Waking",
+            'P' => "Parked",
+            'I' => "Idle",
+            _ => "Unknown",
+        }
+    }
+
+    /// Command line for display, `[name]` without arguments.
+    pub fn command(&self) -> String {
+        if self.cmdline.is_empty() {
+            format!("[{}]", self.name)
+        } else {
+            self.cmdline.join(" ")
+        }
+    }
+}
+
+/// [`SystemSource`] reading a procfs tree directly instead of going through
+/// `sysinfo`.
+///
+/// The root does not have to be the live `/proc`: a container's mounted
+/// procfs, an extracted sosreport or a test fixture work the same way. CPU
+/// usage is computed between consecutive calls, so the first one reports
+/// usage since boot for the whole CPU and 0 for each process.
+pub struct ProcfsSource {
+    root: PathBuf,
+    users: Option<Users>,
+    // Per `cpuN` line: (busy, total) ticks at the previous call.
+    cpu_ticks: HashMap<String, (u64, u64)>,
+    // Per PID: CPU ticks at the previous call to `processes`.
+    process_ticks: HashMap<u32, u64>,
+    last_process_sample: Option<Instant>,
+}
+
+impl ProcfsSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        // UIDs only mean something when the tree comes from this machine.
+        let users = (root == Path::new("/proc")).then(Users::new_with_refreshed_list);
+        ProcfsSource {
+            root,
+            users,
+            cpu_ticks: HashMap::new(),
+            process_ticks: HashMap::new(),
+            last_process_sample: None,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// PIDs of every process directory under the root, ascending.
+    pub fn pids(&self) -> Result<Vec<u32>> {
+        let mut pids = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            if let Some(pid) = entry?
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse().ok())
+            {
+                pids.push(pid);
+            }
+        }
+        pids.sort_unstable();
+        Ok(pids)
+    }
+
+    /// Reads a single process, [`Error::ProcessVanished`] if it is gone.
+    pub fn process(&self, pid: u32) -> Result<ProcessDetails> {
+        let dir = self.root.join(pid.to_string());
+        let stat = fs::read_to_string(dir.join("stat")).map_err(|err| vanished(err, pid))?;
+        let mut details = parse_stat(&stat)
+            .ok_or_else(|| Error::Parse(format!("{}: invalid stat", dir.display())))?;
+        if let Ok(status) = fs::read_to_string(dir.join("status")) {
+            apply_status(&mut details, &status);
+        }
+        if let Ok(cmdline) = fs::read(dir.join("cmdline")) {
+            details.cmdline = cmdline
+                .split(|byte| *byte == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect();
+        }
+        details.io = fs::read_to_string(dir.join("io"))
+            .ok()
+            .and_then(|io| parse_io(&io));
+        Ok(details)
+    }
+
+    /// Reads the memory breakdown of a process. Falls back to summing
+    /// `smaps` on kernels older than 4.14, which have no `smaps_rollup`.
+    /// Other users' processes usually need root.
+    pub fn process_memory(&self, pid: u32) -> Result<ProcMemory> {
+        let dir = self.root.join(pid.to_string());
+        let smaps = match fs::read_to_string(dir.join("smaps_rollup")) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound && dir.exists() => {
+                fs::read_to_string(dir.join("smaps"))
+            }
+            result => result,
+        };
+        let smaps = smaps.map_err(|err| vanished(err, pid))?;
+        // Kernel threads have an empty file and come out as all zeros.
+        Ok(parse_smaps(&smaps))
+    }
+
+    /// Reads every process, skipping the ones that exit while being read.
+    pub fn process_details(&self) -> Result<Vec<ProcessDetails>> {
+        let mut processes = Vec::new();
+        for pid in self.pids()? {
+            match self.process(pid) {
+                Ok(details) => processes.push(details),
+                Err(Error::ProcessVanished(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(processes)
+    }
+
+    fn read(&self, name: &str) -> Result<String> {
+        Ok(fs::read_to_string(self.root.join(name))?)
+    }
+}
+
+impl SystemSource for ProcfsSource {
+    fn cpu(&mut self) -> Result<CpuInfo> {
+        let stat = self.read("stat")?;
+        let cpuinfo = self.read("cpuinfo").unwrap_or_default();
+        let descriptions = parse_cpuinfo(&cpuinfo);
+        let mut global_usage = 0.0;
+        let mut cores = Vec::new();
+        for line in stat.lines().filter(|line| line.starts_with("cpu")) {
+            let mut fields = line.split_whitespace();
+            let Some(name) = fields.next() else {
+                continue;
+            };
+            let ticks: Vec<u64> = fields.filter_map(|field| field.parse().ok()).collect();
+            // user nice system idle iowait irq softirq steal; guest time is
+            // already part of user and nice.
+            let total: u64 = ticks.iter().take(8).sum();
+            let idle = ticks.get(3).copied().unwrap_or(0) + ticks.get(4).copied().unwrap_or(0);
+            let busy = total - idle.min(total);
+            let (previous_busy, previous_total) = self
+                .cpu_ticks
+                .insert(name.to_string(), (busy, total))
+                .unwrap_or((0, 0));
+            let elapsed = total.saturating_sub(previous_total);
+            let usage = if elapsed == 0 {
+                0.0
+            } else {
+                busy.saturating_sub(previous_busy) as f32 * 100.0 / elapsed as f32
+            };
+            if name == "cpu" {
+                global_usage = usage;
+                continue;
+            }
+            let index: usize = name["cpu".len()..].parse().unwrap_or(cores.len());
+            let description = descriptions.get(index).cloned().unwrap_or_default();
+            cores.push(CoreInfo {
+                name: name.to_string(),
+                brand: description.brand,
+                vendor_id: description.vendor_id,
+                frequency: description.frequency,
+                usage,
+            });
+        }
+        Ok(CpuInfo {
+            physical_core_count: physical_core_count(&cpuinfo),
+            global_usage,
+            cores,
+        })
+    }
+
+    fn memory(&mut self) -> Result<MemoryInfo> {
+        let meminfo = self.read("meminfo")?;
+        let mut values = HashMap::new();
+        for line in meminfo.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let kib = value
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<u64>()
+                .unwrap_or(0);
+            values.insert(key.trim(), kib * 1024);
+        }
+        let get = |key: &str| values.get(key).copied().unwrap_or(0);
+        let total_memory = get("MemTotal");
+        // Kernels before 3.14 have no MemAvailable.
+        let available_memory = values
+            .get("MemAvailable")
+            .copied()
+            .unwrap_or_else(|| get("MemFree") + get("Buffers") + get("Cached"));
+        let total_swap = get("SwapTotal");
+        let free_swap = get("SwapFree");
+        Ok(MemoryInfo {
+            total_memory,
+            free_memory: get("MemFree"),
+            available_memory,
+            used_memory: total_memory.saturating_sub(available_memory),
+            total_swap,
+            free_swap,
+            used_swap: total_swap.saturating_sub(free_swap),
+        })
+    }
+
+    fn processes(&mut self) -> Result<Vec<ProcessRow>> {
+        let details = self.process_details()?;
+        let now = Instant::now();
+        let elapsed = self
+            .last_process_sample
+            .replace(now)
+            .map(|previous| now.duration_since(previous).as_secs_f64());
+        let mut ticks = HashMap::with_capacity(details.len());
+        let mut rows = Vec::with_capacity(details.len());
+        for process in details {
+            let cpu_usage = match (elapsed, self.process_ticks.get(&process.pid)) {
+                (Some(elapsed), Some(previous)) if elapsed > 0.0 => {
+                    let seconds =
+                        process.cpu_ticks.saturating_sub(*previous) as f64 / CLOCK_TICKS_PER_SECOND;
+                    (seconds * 100.0 / elapsed) as f32
+                }
+                _ => 0.0,
+            };
+            ticks.insert(process.pid, process.cpu_ticks);
+            let user = process.uid.and_then(|uid| {
+                let users = self.users.as_ref()?;
+                let uid = Uid::try_from(uid as usize).ok()?;
+                users
+                    .get_user_by_id(&uid)
+                    .map(|user| user.name().to_string())
+            });
+            rows.push(ProcessRow {
+                pid: process.pid,
+                ppid: process.ppid,
+                user,
+                cpu_usage,
+                memory: process.rss,
+                state: process.state_name().to_string(),
+                threads: process.threads,
+                command: process.command(),
+                name: process.name,
+            });
+        }
+        self.process_ticks = ticks;
+        Ok(rows)
+    }
+
+    /// Only the live `/proc` has batteries, read through the battery
+    /// backend. Any other root reports [`Error::NoBatteries`].
+    fn batteries(&mut self) -> Result<Vec<BatteryDetails>> {
+        if self.root == Path::new("/proc") {
+            get_batteries()
+        } else {
+            Err(Error::NoBatteries)
+        }
+    }
+}
+
+fn vanished(err: io::Error, pid: u32) -> Error {
+    match err.kind() {
+        io::ErrorKind::NotFound => Error::ProcessVanished(pid),
+        io::ErrorKind::PermissionDenied => Error::PermissionDenied(format!("/proc/{}", pid)),
+        _ => Error::Io(err),
+    }
+}
+
+fn parse_stat(stat: &str) -> Option<ProcessDetails> {
+    // The name is in parentheses and may itself contain spaces and ')'.
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let pid = stat[..open].trim().parse().ok()?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+    let field = |index: usize| {
+        fields
+            .get(index)
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    let ppid = field(1)? as u32;
+    Some(ProcessDetails {
+        pid,
+        ppid: (ppid != 0).then_some(ppid),
+        name,
+        state: fields.first()?.chars().next()?,
+        uid: None,
+        cmdline: Vec::new(),
+        nice: fields.get(16)?.parse().ok()?,
+        threads: field(17)? as usize,
+        minor_faults: field(7)?,
+        major_faults: field(9)?,
+        voluntary_ctxt_switches: None,
+        nonvoluntary_ctxt_switches: None,
+        cpu_ticks: field(11)? + field(12)?,
+        start_ticks: field(19)?,
+        // Pages; corrected from `status`, which reports kB.
+        rss: field(21)? * 4096,
+        io: None,
+    })
+}
+
+fn apply_status(details: &mut ProcessDetails, status: &str) {
+    for line in status.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let first = value.split_whitespace().next();
+        let number = first.and_then(|value| value.parse::<u64>().ok());
+        match key {
+            "Uid" => details.uid = number.map(|uid| uid as u32),
+            "VmRSS" => {
+                if let Some(kib) = number {
+                    details.rss = kib * 1024;
+                }
+            }
+            "voluntary_ctxt_switches" => details.voluntary_ctxt_switches = number,
+            "nonvoluntary_ctxt_switches" => details.nonvoluntary_ctxt_switches = number,
+            _ => {}
+        }
+    }
+}
+
+fn parse_io(io: &str) -> Option<ProcIo> {
+    let mut counters = ProcIo::default();
+    let mut seen = false;
+    for line in io.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse() else {
+            continue;
+        };
+        let slot = match key {
+            "rchar" => &mut counters.rchar,
+            "wchar" => &mut counters.wchar,
+            "syscr" => &mut counters.syscr,
+            "syscw" => &mut counters.syscw,
+            "read_bytes" => &mut counters.read_bytes,
+            "write_bytes" => &mut counters.write_bytes,
+            "cancelled_write_bytes" => &mut counters.cancelled_write_bytes,
+            _ => continue,
+        };
+        *slot = value;
+        seen = true;
+    }
+    seen.then_some(counters)
+}
+
+// Sums the fields of every mapping, so that it reads `smaps` as well as
+// the single `[rollup]` entry of `smaps_rollup`.
+fn parse_smaps(smaps: &str) -> ProcMemory {
+    let mut memory = ProcMemory::default();
+    for line in smaps.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(kib) = value
+            .trim()
+            .strip_suffix("kB")
+            .and_then(|kib| kib.trim().parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let slot = match key {
+            "Rss" => &mut memory.rss,
+            "Pss" => &mut memory.pss,
+            "Shared_Clean" => &mut memory.shared_clean,
+            "Shared_Dirty" => &mut memory.shared_dirty,
+            "Private_Clean" => &mut memory.private_clean,
+            "Private_Dirty" => &mut memory.private_dirty,
+            "Anonymous" => &mut memory.anonymous,
+            "Swap" => &mut memory.swap,
+            "SwapPss" => &mut memory.swap_pss,
+            _ => continue,
+        };
+        *slot += kib * 1024;
+    }
+    memory
+}
+
+#[derive(Debug, Clone, Default)]
+struct CpuDescription {
+    brand: String,
+    vendor_id: String,
+    frequency: u64,
+}
+
+// One entry per `processor` block of /proc/cpuinfo.
+fn parse_cpuinfo(cpuinfo: &str) -> Vec<CpuDescription> {
+    let mut descriptions = Vec::new();
+    for block in cpuinfo
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+    {
+        let mut description = CpuDescription::default();
+        for line in block.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "model name" => description.brand = value.to_string(),
+                "vendor_id" => description.vendor_id = value.to_string(),
+                "cpu MHz" => description.frequency = value.parse::<f64>().unwrap_or(0.0) as u64,
+                _ => {}
+            }
+        }
+        descriptions.push(description);
+    }
+    descriptions
+}
+
+fn physical_core_count(cpuinfo: &str) -> Option<usize> {
+    let mut cores = Vec::new();
+    for block in cpuinfo.split("\n\n") {
+        let mut physical_id = None;
+        let mut core_id = None;
+        for line in block.lines() {
+            match line
+                .split_once(':')
+                .map(|(key, value)| (key.trim(), value.trim()))
+            {
+                Some(("physical id", value)) => physical_id = Some(value),
+                Some(("core id", value)) => core_id = Some(value),
+                _ => {}
+            }
+        }
+        if let (Some(physical_id), Some(core_id)) = (physical_id, core_id)
+            && !cores.contains(&(physical_id, core_id))
+        {
+            cores.push((physical_id, core_id));
+        }
+    }
+    (!cores.is_empty()).then_some(cores.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProcfsSource;
+    use crate::Error;
+    use crate::models_fn_packer::source_helper::{FixtureDir, SystemSource};
+
+    fn add_process(
+        fixture: &FixtureDir,
+        pid: u32,
+        ppid: u32,
+        name: &str,
+        cmdline: &str,
+        utime: u64,
+    ) {
+        fixture.write(
+            &format!("{}/stat", pid),
+            &format!(
+                "{} ({}) S {} {} {} 0 -1 4194304 120 0 3 0 {} 5 0 0 20 -5 2 0 1234 10000 50",
+                pid, name, ppid, pid, pid, utime
+            ),
+        );
+        fixture.write(
+            &format!("{}/status", pid),
+            "Name:\tx\nUid:\t1000\t1000\t1000\t1000\nVmRSS:\t    2048 kB\n\
+             voluntary_ctxt_switches:\t7\nnonvoluntary_ctxt_switches:\t2\n",
+        );
+        fixture.write(&format!("{}/cmdline", pid), &cmdline.replace(' ', "\0"));
+    }
+
+    #[test]
+    fn test_parse_stat_with_odd_name() {
+        let details = super::parse_stat(
+            "42 (tmux: server) (x)) R 1 42 42 0 -1 0 10 0 2 0 30 12 0 0 20 0 3 0 99 0 25",
+        )
+        .unwrap();
+        assert_eq!(details.pid, 42);
+        assert_eq!(details.name, "tmux: server) (x)");
+        assert_eq!(details.state, 'R');
+        assert_eq!(details.ppid, Some(1));
+        assert_eq!(details.minor_faults, 10);
+        assert_eq!(details.major_faults, 2);
+        assert_eq!(details.cpu_ticks, 42);
+        assert_eq!(details.threads, 3);
+        assert_eq!(details.start_ticks, 99);
+        assert_eq!(details.rss, 25 * 4096);
+    }
+
+    #[test]
+    fn test_process_details() {
+        let fixture = FixtureDir::new("procfs_details");
+        add_process(&fixture, 1, 0, "init", "/sbin/init splash", 10);
+        fixture.write(
+            "1/io",
+            "rchar: 100\nwchar: 200\nsyscr: 3\nsyscw: 4\nread_bytes: 4096\n\
+             write_bytes: 8192\ncancelled_write_bytes: 0\n",
+        );
+        add_process(&fixture, 7, 1, "kworker/0:1", "", 0);
+        fixture.write("self/stat", "not a pid directory");
+
+        let source = ProcfsSource::new(&fixture.root);
+        assert_eq!(source.pids().unwrap(), vec![1, 7]);
+        let processes = source.process_details().unwrap();
+
+        let init = &processes[0];
+        assert_eq!(init.ppid, None);
+        assert_eq!(init.cmdline, vec!["/sbin/init", "splash"]);
+        assert_eq!(init.nice, -5);
+        assert_eq!(init.uid, Some(1000));
+        assert_eq!(init.rss, 2048 * 1024);
+        assert_eq!(init.voluntary_ctxt_switches, Some(7));
+        assert_eq!(init.nonvoluntary_ctxt_switches, Some(2));
+        let io = init.io.unwrap();
+        assert_eq!((io.rchar, io.read_bytes, io.write_bytes), (100, 4096, 8192));
+
+        let kworker = &processes[1];
+        assert_eq!(kworker.command(), "[kworker/0:1]");
+        assert_eq!(kworker.io, None);
+
+        assert!(matches!(
+            source.process(99),
+            Err(Error::ProcessVanished(99))
+        ));
+    }
+
+    #[test]
+    fn test_process_memory() {
+        let fixture = FixtureDir::new("procfs_process_memory");
+        add_process(&fixture, 10, 1, "worker", "gunicorn", 0);
+        fixture.write(
+            "10/smaps_rollup",
+            "55559f8d8000-7ffc84308000 ---p 00000000 00:00 0    [rollup]\n\
+             Rss:                1436 kB\nPss:                 370 kB\n\
+             Pss_Anon:            104 kB\nShared_Clean:       1292 kB\n\
+             Shared_Dirty:          0 kB\nPrivate_Clean:        40 kB\n\
+             Private_Dirty:       104 kB\nAnonymous:           104 kB\n\
+             Swap:                 16 kB\nSwapPss:               8 kB\n",
+        );
+        // Without smaps_rollup the mappings of smaps are summed.
+        add_process(&fixture, 11, 1, "old", "old", 0);
+        fixture.write(
+            "11/smaps",
+            "00400000-00452000 r-xp 00000000 08:02 173521  /usr/bin/old\n\
+             Rss:                  8 kB\nPss:                  4 kB\n\
+             00652000-00655000 rw-p 00052000 08:02 173521  /usr/bin/old\n\
+             Rss:                 12 kB\nPss:                 12 kB\nAnonymous:           12 kB\n",
+        );
+        let source = ProcfsSource::new(&fixture.root);
+
+        let memory = source.process_memory(10).unwrap();
+        assert_eq!(memory.rss, 1436 * 1024);
+        assert_eq!(memory.pss, 370 * 1024);
+        assert_eq!(memory.uss(), 144 * 1024);
+        assert_eq!(memory.shared(), 1292 * 1024);
+        assert_eq!(memory.file_backed(), 1332 * 1024);
+        assert_eq!(memory.swap, 16 * 1024);
+        assert_eq!(memory.swap_pss, 8 * 1024);
+
+        let memory = source.process_memory(11).unwrap();
+        assert_eq!(memory.rss, 20 * 1024);
+        assert_eq!(memory.pss, 16 * 1024);
+        assert_eq!(memory.anonymous, 12 * 1024);
+
+        assert!(matches!(
+            source.process_memory(12),
+            Err(Error::ProcessVanished(12))
+        ));
+    }
+
+    #[test]
+    fn test_processes_cpu_usage() {
+        let fixture = FixtureDir::new("procfs_usage");
+        add_process(&fixture, 1, 0, "init", "/sbin/init", 10);
+        let mut source = ProcfsSource::new(&fixture.root);
+
+        let first = source.processes().unwrap();
+        assert_eq!(first[0].cpu_usage, 0.0);
+        assert_eq!(first[0].state, "Sleeping");
+        assert_eq!(first[0].user, None);
+        assert_eq!(first[0].command, "/sbin/init");
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        add_process(&fixture, 1, 0, "init", "/sbin/init", 1010);
+        let second = source.processes().unwrap();
+        assert!(second[0].cpu_usage > 100.0);
+
+        let summary = source.process_summary().unwrap();
+        assert_eq!(summary.process_count, 1);
+        assert_eq!(summary.root_process_count, 1);
+    }
+
+    #[test]
+    fn test_memory() {
+        let fixture = FixtureDir::new("procfs_memory");
+        fixture.write(
+            "meminfo",
+            "MemTotal:       1000 kB\nMemFree:         200 kB\nMemAvailable:    600 kB\n\
+             SwapTotal:       500 kB\nSwapFree:        400 kB\n",
+        );
+        let memory = ProcfsSource::new(&fixture.root).memory().unwrap();
+
+        assert_eq!(memory.total_memory, 1000 * 1024);
+        assert_eq!(memory.free_memory, 200 * 1024);
+        assert_eq!(memory.available_memory, 600 * 1024);
+        assert_eq!(memory.used_memory, 400 * 1024);
+        assert_eq!(memory.used_swap, 100 * 1024);
+    }
+
+    #[test]
+    fn test_cpu() {
+        let fixture = FixtureDir::new("procfs_cpu");
+        fixture.write(
+            "cpuinfo",
+            "processor\t: 0\nvendor_id\t: GenuineIntel\nmodel name\t: Xeon\ncpu MHz\t\t: 2000.000\n\
+             physical id\t: 0\ncore id\t\t: 0\n\n\
+             processor\t: 1\nvendor_id\t: GenuineIntel\nmodel name\t: Xeon\ncpu MHz\t\t: 2100.500\n\
+             physical id\t: 0\ncore id\t\t: 0\n",
+        );
+        fixture.write(
+            "stat",
+            "cpu  100 0 100 800 0 0 0 0 0 0\ncpu0 50 0 50 400 0 0 0 0 0 0\n\
+             cpu1 50 0 50 400 0 0 0 0 0 0\nintr 1 2 3\n",
+        );
+        let mut source = ProcfsSource::new(&fixture.root);
+
+        let cpu = source.cpu().unwrap();
+        assert_eq!(cpu.physical_core_count, Some(1));
+        assert_eq!(cpu.logical_core_count(), 2);
+        assert_eq!(cpu.cores[1].frequency, 2100);
+        assert_eq!(cpu.cores[0].brand, "Xeon");
+        assert_eq!(cpu.global_usage, 20.0);
+
+        fixture.write(
+            "stat",
+            "cpu  200 0 100 900 0 0 0 0 0 0\ncpu0 150 0 50 400 0 0 0 0 0 0\n\
+             cpu1 50 0 50 500 0 0 0 0 0 0\n",
+        );
+        let cpu = source.cpu().unwrap();
+        assert_eq!(cpu.global_usage, 50.0);
+        assert_eq!(cpu.cores[0].usage, 100.0);
+        assert_eq!(cpu.cores[1].usage, 0.0);
+    }
+
+    #[test]
+    fn test_live_proc() {
+        let mut source = ProcfsSource::new("/proc");
+        let own_pid = std::process::id();
+
+        let own = source.process(own_pid).unwrap();
+        assert!(own.threads >= 1);
+        assert!(source.memory().unwrap().total_memory > 0);
+        assert!(
+            source
+                .processes()
+                .unwrap()
+                .iter()
+                .any(|row| row.pid == own_pid)
+        );
+    }
+}
@@ -0,0 +1,9 @@
+mod packer;
+
+pub use packer::KEYFRAME_INTERVAL;
+pub use packer::LogHeader;
+pub use packer::LogRecord;
+pub use packer::ProcessDelta;
+pub use packer::SnapshotReader;
+pub use packer::SnapshotWriter;
+pub use packer::read_snapshot_log;
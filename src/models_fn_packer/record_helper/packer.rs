@@ -0,0 +1,376 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::iter::Peekable;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::models_fn_packer::battery_helper::BatteryDetails;
+use crate::models_fn_packer::source_helper::Snapshot;
+use crate::models_fn_packer::sys_info_helper::{CpuInfo, MemoryInfo};
+use crate::models_fn_packer::top_helper::ProcessRow;
+use crate::{Error, Result};
+
+const LOG_FORMAT: &str = "phlog";
+const LOG_VERSION: u32 = 1;
+
+/// A full process table is written every this many records, so that a
+/// damaged record only affects the ticks up to the next one: the reader
+/// skips them and resumes at the next full table.
+pub const KEYFRAME_INTERVAL: u64 = 300;
+
+/// First line of a snapshot log.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LogHeader {
+    pub format: String,
+    pub version: u32,
+    /// Interval the recording was made with, in milliseconds.
+    pub interval_ms: u64,
+}
+
+/// Process table of one record, relative to the previous record.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessDelta {
+    /// Every process, replacing whatever was known before.
+    Full(Vec<ProcessRow>),
+    /// Processes that are new or differ in any field, and PIDs that exited.
+    Changes {
+        changed: Vec<ProcessRow>,
+        removed: Vec<u32>,
+    },
+}
+
+/// One line of a snapshot log after the header.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LogRecord {
+    pub timestamp_ms: u64,
+    pub cpu: CpuInfo,
+    pub memory: MemoryInfo,
+    pub batteries: Vec<BatteryDetails>,
+    pub processes: ProcessDelta,
+}
+
+/// Appends snapshots to a newline-delimited JSON log, delta-encoding the
+/// process table against the previous snapshot.
+pub struct SnapshotWriter<W: Write> {
+    writer: W,
+    previous: HashMap<u32, ProcessRow>,
+    records: u64,
+}
+
+impl SnapshotWriter<BufWriter<File>> {
+    /// Creates or truncates the log at `path`.
+    pub fn create(path: &Path, interval: Duration) -> Result<Self> {
+        SnapshotWriter::new(BufWriter::new(File::create(path)?), interval)
+    }
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Writes the header to `writer`.
+    pub fn new(mut writer: W, interval: Duration) -> Result<Self> {
+        let header = LogHeader {
+            format: LOG_FORMAT.to_string(),
+            version: LOG_VERSION,
+            interval_ms: interval.as_millis() as u64,
+        };
+        write_line(&mut writer, &header)?;
+        Ok(SnapshotWriter {
+            writer,
+            previous: HashMap::new(),
+            records: 0,
+        })
+    }
+
+    /// Appends `snapshot` and flushes, so that an interrupted recording
+    /// keeps every complete record.
+    pub fn write(&mut self, snapshot: &Snapshot) -> Result<()> {
+        let processes = if self.records.is_multiple_of(KEYFRAME_INTERVAL) {
+            ProcessDelta::Full(snapshot.processes.clone())
+        } else {
+            let changed = snapshot
+                .processes
+                .iter()
+                .filter(|row| self.previous.get(&row.pid) != Some(row))
+                .cloned()
+                .collect();
+            let current: HashSet<u32> = snapshot.processes.iter().map(|row| row.pid).collect();
+            let mut removed: Vec<u32> = self
+                .previous
+                .keys()
+                .filter(|pid| !current.contains(pid))
+                .copied()
+                .collect();
+            removed.sort_unstable();
+            ProcessDelta::Changes { changed, removed }
+        };
+        let record = LogRecord {
+            timestamp_ms: snapshot.timestamp_ms,
+            cpu: snapshot.cpu.clone(),
+            memory: snapshot.memory,
+            batteries: snapshot.batteries.clone(),
+            processes,
+        };
+        write_line(&mut self.writer, &record)?;
+        self.writer.flush()?;
+        self.previous = snapshot
+            .processes
+            .iter()
+            .map(|row| (row.pid, row.clone()))
+            .collect();
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Number of snapshots written so far.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+}
+
+fn write_line<W: Write, T: serde::Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    let line = serde_json::to_string(value).map_err(|err| Error::Parse(err.to_string()))?;
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Reads a log written by [`SnapshotWriter`] back into full snapshots.
+///
+/// A truncated last line, as left by a killed recording, ends the log
+/// instead of failing it. A damaged record anywhere else is skipped along
+/// with the deltas that follow it, up to the next full process table; see
+/// [`SnapshotReader::skipped`].
+pub struct SnapshotReader<R: BufRead> {
+    header: LogHeader,
+    lines: Peekable<Lines<R>>,
+    // Ordered by PID, like the reconstructed snapshots.
+    processes: BTreeMap<u32, ProcessRow>,
+    // Set after a damaged record, until the next full process table.
+    resyncing: bool,
+    skipped: usize,
+}
+
+impl SnapshotReader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Self> {
+        SnapshotReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead> SnapshotReader<R> {
+    /// Reads and checks the header.
+    pub fn new(reader: R) -> Result<Self> {
+        let mut lines = reader.lines();
+        let first = lines
+            .next()
+            .transpose()?
+            .ok_or_else(|| Error::Parse("empty snapshot log".to_string()))?;
+        let header: LogHeader = serde_json::from_str(&first)
+            .map_err(|_| Error::Parse("not a snapshot log".to_string()))?;
+        if header.format != LOG_FORMAT || header.version != LOG_VERSION {
+            return Err(Error::Parse(format!(
+                "unsupported log format {} version {}",
+                header.format, header.version
+            )));
+        }
+        Ok(SnapshotReader {
+            header,
+            lines: lines.peekable(),
+            processes: BTreeMap::new(),
+            resyncing: false,
+            skipped: 0,
+        })
+    }
+
+    pub fn header(&self) -> &LogHeader {
+        &self.header
+    }
+
+    /// Number of records skipped so far, because they were damaged or were
+    /// deltas against a damaged record.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    fn apply(&mut self, record: LogRecord) -> Snapshot {
+        match record.processes {
+            ProcessDelta::Full(rows) => {
+                self.processes = rows.into_iter().map(|row| (row.pid, row)).collect();
+            }
+            ProcessDelta::Changes { changed, removed } => {
+                for pid in removed {
+                    self.processes.remove(&pid);
+                }
+                for row in changed {
+                    self.processes.insert(row.pid, row);
+                }
+            }
+        }
+        Snapshot {
+            timestamp_ms: record.timestamp_ms,
+            cpu: record.cpu,
+            memory: record.memory,
+            processes: self.processes.values().cloned().collect(),
+            batteries: record.batteries,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SnapshotReader<R> {
+    type Item = Result<Snapshot>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<LogRecord>(&line) {
+                Ok(record) => {
+                    if self.resyncing && !matches!(record.processes, ProcessDelta::Full(_)) {
+                        self.skipped += 1;
+                        continue;
+                    }
+                    self.resyncing = false;
+                    return Some(Ok(self.apply(record)));
+                }
+                Err(err) if err.is_eof() && self.lines.peek().is_none() => return None,
+                Err(_) => {
+                    self.resyncing = true;
+                    self.skipped += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Reads every snapshot of the log at `path`.
+pub fn read_snapshot_log(path: &Path) -> Result<Vec<Snapshot>> {
+    SnapshotReader::open(path)?.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{LogRecord, ProcessDelta, SnapshotReader, SnapshotWriter};
+    use crate::models_fn_packer::source_helper::Snapshot;
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    fn row(pid: u32, cpu_usage: f32) -> ProcessRow {
+        ProcessRow {
+            ppid: Some(1),
+            user: Some("root".to_string()),
+            cpu_usage,
+            memory: 4096,
+            command: "worker --fast".to_string(),
+            ..ProcessRow::fixture(pid, "worker")
+        }
+    }
+
+    fn snapshot(timestamp_ms: u64, processes: Vec<ProcessRow>) -> Snapshot {
+        Snapshot {
+            timestamp_ms,
+            processes,
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let snapshots = vec![
+            snapshot(1000, vec![row(1, 0.0), row(2, 5.0), row(3, 0.0)]),
+            snapshot(2000, vec![row(1, 0.0), row(2, 7.5), row(4, 1.0)]),
+            snapshot(3000, vec![row(1, 0.0), row(4, 1.0)]),
+        ];
+        let mut buffer = Vec::new();
+        let mut writer = SnapshotWriter::new(&mut buffer, Duration::from_secs(1)).unwrap();
+        for snapshot in &snapshots {
+            writer.write(snapshot).unwrap();
+        }
+        assert_eq!(writer.records(), 3);
+
+        let reader = SnapshotReader::new(buffer.as_slice()).unwrap();
+        assert_eq!(reader.header().interval_ms, 1000);
+        let read: Vec<Snapshot> = reader.collect::<crate::Result<_>>().unwrap();
+        assert_eq!(read, snapshots);
+    }
+
+    #[test]
+    fn test_delta_encoding() {
+        let mut buffer = Vec::new();
+        let mut writer = SnapshotWriter::new(&mut buffer, Duration::from_secs(1)).unwrap();
+        writer
+            .write(&snapshot(1000, vec![row(1, 0.0), row(2, 5.0), row(3, 0.0)]))
+            .unwrap();
+        writer
+            .write(&snapshot(2000, vec![row(1, 0.0), row(2, 7.5), row(4, 1.0)]))
+            .unwrap();
+
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let first: LogRecord = serde_json::from_str(lines[1]).unwrap();
+        assert!(matches!(first.processes, ProcessDelta::Full(ref rows) if rows.len() == 3));
+        let second: LogRecord = serde_json::from_str(lines[2]).unwrap();
+        let ProcessDelta::Changes { changed, removed } = second.processes else {
+            panic!("Second record should be a delta");
+        };
+        let changed: Vec<u32> = changed.iter().map(|row| row.pid).collect();
+        assert_eq!(changed, vec![2, 4]);
+        assert_eq!(removed, vec![3]);
+    }
+
+    #[test]
+    fn test_truncated_log() {
+        let mut buffer = Vec::new();
+        let mut writer = SnapshotWriter::new(&mut buffer, Duration::from_secs(1)).unwrap();
+        writer.write(&snapshot(1000, vec![row(1, 0.0)])).unwrap();
+        writer.write(&snapshot(2000, vec![row(1, 2.0)])).unwrap();
+        buffer.truncate(buffer.len() - 20);
+
+        let reader = SnapshotReader::new(buffer.as_slice()).unwrap();
+        let read: Vec<Snapshot> = reader.collect::<crate::Result<_>>().unwrap();
+        assert_eq!(read.len(), 1);
+    }
+
+    #[test]
+    fn test_damaged_record() {
+        let mut buffer = Vec::new();
+        let mut writer = SnapshotWriter::new(&mut buffer, Duration::from_secs(1)).unwrap();
+        for tick in 0..super::KEYFRAME_INTERVAL + 2 {
+            writer
+                .write(&snapshot(tick * 1000, vec![row(1, tick as f32)]))
+                .unwrap();
+        }
+        let text = String::from_utf8(buffer).unwrap();
+        let mut lines: Vec<&str> = text.lines().collect();
+        // Header, then one line per record.
+        lines[2] = "{\"timestamp_ms\": garbage";
+        let damaged = lines.join("\n");
+
+        let mut reader = SnapshotReader::new(damaged.as_bytes()).unwrap();
+        let read: Vec<Snapshot> = reader.by_ref().collect::<crate::Result<_>>().unwrap();
+        // Every delta up to the next full table depends on the damaged one.
+        let timestamps: Vec<u64> = read.iter().map(|s| s.timestamp_ms).collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                0,
+                super::KEYFRAME_INTERVAL * 1000,
+                (super::KEYFRAME_INTERVAL + 1) * 1000
+            ]
+        );
+        assert_eq!(reader.skipped(), super::KEYFRAME_INTERVAL as usize - 1);
+        assert_eq!(read[2].processes[0].cpu_usage, 301.0);
+    }
+
+    #[test]
+    fn test_not_a_log() {
+        assert!(SnapshotReader::new("hello\n".as_bytes()).is_err());
+        assert!(SnapshotReader::new("".as_bytes()).is_err());
+    }
+}
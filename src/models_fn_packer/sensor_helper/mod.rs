@@ -0,0 +1,7 @@
+mod packer;
+
+pub use packer::SensorKind;
+pub use packer::SensorReading;
+pub use packer::cpu_core_id;
+pub use packer::get_sensor_info;
+pub use packer::read_hwmon;
@@ -0,0 +1,232 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::Result;
+
+/// What a temperature sensor measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SensorKind {
+    Cpu,
+    /// NVMe drives, and SATA drives through the `drivetemp` driver.
+    Storage,
+    Battery,
+    Other,
+}
+
+/// One temperature reading, all in degrees Celsius.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorReading {
+    /// Driver name, e.g. `coretemp` or `nvme`.
+    pub chip: String,
+    /// Device the chip belongs to, e.g. `nvme0`, to tell identical chips
+    /// apart.
+    pub device: Option<String>,
+    /// e.g. `Package id 0`, `Core 3` or `Composite`.
+    pub label: String,
+    pub kind: SensorKind,
+    /// Physical core the reading belongs to, for per-core CPU sensors.
+    pub core: Option<u32>,
+    pub temperature: f32,
+    /// Temperature the hardware starts throttling at.
+    pub max: Option<f32>,
+    /// Temperature the hardware shuts down at.
+    pub critical: Option<f32>,
+}
+
+const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+/// Temperatures of the CPU, drives and batteries, CPU first. Empty where the
+/// platform exposes none.
+pub fn get_sensor_info() -> Result<Vec<SensorReading>> {
+    let mut readings = read_hwmon(Path::new(HWMON_ROOT))?;
+    if !readings
+        .iter()
+        .any(|reading| reading.kind == SensorKind::Battery)
+    {
+        readings.extend(battery_temperatures());
+    }
+    readings.sort_by_key(|reading| reading.kind);
+    Ok(readings)
+}
+
+// A missing or unreadable battery backend only means no battery reading.
+fn battery_temperatures() -> Vec<SensorReading> {
+    let Ok(batteries) = battery::Manager::new().and_then(|manager| manager.batteries()) else {
+        return Vec::new();
+    };
+    batteries
+        .flatten()
+        .enumerate()
+        .filter_map(|(index, battery)| {
+            Some(SensorReading {
+                chip: "battery".to_string(),
+                device: Some(format!("BAT{}", index)),
+                label: battery.model().unwrap_or("Battery").to_string(),
+                kind: SensorKind::Battery,
+                core: None,
+                // Kelvin.
+                temperature: battery.temperature()?.value - 273.15,
+                max: None,
+                critical: None,
+            })
+        })
+        .collect()
+}
+
+/// Temperature sensors of every chip under a `/sys/class/hwmon` tree, in
+/// chip and sensor order. A missing tree yields no readings.
+pub fn read_hwmon(root: &Path) -> Result<Vec<SensorReading>> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut chips: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    chips.sort_by_key(|path| trailing_number(&path.to_string_lossy()));
+
+    let mut readings = Vec::new();
+    for dir in chips {
+        let Some(chip) = read_trimmed(&dir.join("name")) else {
+            continue;
+        };
+        let device = fs::read_link(dir.join("device"))
+            .ok()
+            .and_then(|target| Some(target.file_name()?.to_string_lossy().into_owned()));
+        let kind = chip_kind(&chip);
+        let mut sensors: Vec<u32> = fs::read_dir(&dir)?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                let name = name.to_str()?;
+                name.strip_prefix("temp")?
+                    .strip_suffix("_input")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        sensors.sort_unstable();
+        for sensor in sensors {
+            let value = |suffix: &str| {
+                read_trimmed(&dir.join(format!("temp{}_{}", sensor, suffix)))?
+                    .parse::<i64>()
+                    .ok()
+                    .map(|millidegrees| millidegrees as f32 / 1000.0)
+            };
+            // Reading fails for sensors of devices that are powered down.
+            let Some(temperature) = value("input") else {
+                continue;
+            };
+            let label = read_trimmed(&dir.join(format!("temp{}_label", sensor)))
+                .unwrap_or_else(|| format!("temp{}", sensor));
+            readings.push(SensorReading {
+                core: (kind == SensorKind::Cpu)
+                    .then(|| label.strip_prefix("Core ")?.parse().ok())
+                    .flatten(),
+                chip: chip.clone(),
+                device: device.clone(),
+                label,
+                kind,
+                temperature,
+                max: value("max"),
+                critical: value("crit"),
+            });
+        }
+    }
+    Ok(readings)
+}
+
+/// Physical core a logical CPU such as `cpu3` runs on (Linux).
+pub fn cpu_core_id(cpu: &str) -> Option<u32> {
+    read_trimmed(
+        &Path::new("/sys/devices/system/cpu")
+            .join(cpu)
+            .join("topology/core_id"),
+    )?
+    .parse()
+    .ok()
+}
+
+fn chip_kind(chip: &str) -> SensorKind {
+    match chip {
+        "coretemp" | "k10temp" | "zenpower" | "cpu_thermal" => SensorKind::Cpu,
+        "nvme" | "drivetemp" => SensorKind::Storage,
+        _ if chip.starts_with("BAT") || chip.starts_with("battery") => SensorKind::Battery,
+        _ => SensorKind::Other,
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+}
+
+// `hwmon10` sorts after `hwmon9`.
+fn trailing_number(name: &str) -> u32 {
+    let digits = name.len() - name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    name[name.len() - digits..].parse().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SensorKind, read_hwmon};
+    use crate::models_fn_packer::source_helper::FixtureDir;
+
+    #[test]
+    fn test_read_hwmon() {
+        let fixture = FixtureDir::new("hwmon");
+        fixture.write("hwmon10/name", "acpitz\n");
+        fixture.write("hwmon10/temp1_input", "27800\n");
+        fixture.write("hwmon2/name", "nvme\n");
+        fixture.write("hwmon2/temp1_input", "38850\n");
+        fixture.write("hwmon2/temp1_label", "Composite\n");
+        fixture.write("hwmon2/temp1_max", "81850\n");
+        fixture.write("hwmon2/temp1_crit", "84850\n");
+        fixture.write("hwmon3/name", "coretemp\n");
+        fixture.write("hwmon3/temp1_input", "54000\n");
+        fixture.write("hwmon3/temp1_label", "Package id 0\n");
+        fixture.write("hwmon3/temp1_crit", "100000\n");
+        fixture.write("hwmon3/temp2_input", "52000\n");
+        fixture.write("hwmon3/temp2_label", "Core 0\n");
+        fixture.write("hwmon3/temp10_input", "51000\n");
+        fixture.write("hwmon3/temp10_label", "Core 8\n");
+        // Sensor of a device that is asleep.
+        fixture.write("hwmon3/temp3_input", "");
+        fixture.write("hwmon3/temp3_label", "Core 1\n");
+        fixture.write("hwmon4/fan1_input", "1200\n");
+
+        let readings = read_hwmon(&fixture.root).unwrap();
+        let labels: Vec<&str> = readings.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["Composite", "Package id 0", "Core 0", "Core 8", "temp1"]
+        );
+
+        let nvme = &readings[0];
+        assert_eq!(nvme.kind, SensorKind::Storage);
+        assert_eq!(nvme.temperature, 38.85);
+        assert_eq!(nvme.max, Some(81.85));
+        assert_eq!(nvme.critical, Some(84.85));
+
+        let package = &readings[1];
+        assert_eq!(package.kind, SensorKind::Cpu);
+        assert_eq!(package.core, None);
+        assert_eq!(package.max, None);
+        assert_eq!(package.critical, Some(100.0));
+        assert_eq!(readings[3].core, Some(8));
+
+        assert_eq!(readings[4].chip, "acpitz");
+        assert_eq!(readings[4].kind, SensorKind::Other);
+    }
+
+    #[test]
+    fn test_read_missing_hwmon() {
+        let missing = std::env::temp_dir().join("proc_hunter_no_such_hwmon");
+        assert!(read_hwmon(&missing).unwrap().is_empty());
+    }
+}
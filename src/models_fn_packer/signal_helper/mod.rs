@@ -0,0 +1,11 @@
+mod packer;
+
+pub use packer::KillOutcome;
+pub use packer::KillReport;
+pub use packer::ReniceOutcome;
+pub use packer::ReniceReport;
+pub use packer::is_signal_supported;
+pub use packer::parse_signal;
+pub use packer::renice;
+pub use packer::send_signal;
+pub use packer::signal_name;
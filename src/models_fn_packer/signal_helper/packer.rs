@@ -0,0 +1,372 @@
+use std::fmt;
+
+use sysinfo::{Pid, ProcessesToUpdate, SUPPORTED_SIGNALS, Signal};
+
+use crate::Result;
+use crate::models_fn_packer::sys_info_helper::get_system;
+
+// Conventional names without the `SIG` prefix, as accepted by kill(1).
+const SIGNAL_NAMES: [(&str, Signal); 32] = [
+    ("HUP", Signal::Hangup),
+    ("INT", Signal::Interrupt),
+    ("QUIT", Signal::Quit),
+    ("ILL", Signal::Illegal),
+    ("TRAP", Signal::Trap),
+    ("ABRT", Signal::Abort),
+    ("IOT", Signal::IOT),
+    ("BUS", Signal::Bus),
+    ("FPE", Signal::FloatingPointException),
+    ("KILL", Signal::Kill),
+    ("USR1", Signal::User1),
+    ("SEGV", Signal::Segv),
+    ("USR2", Signal::User2),
+    ("PIPE", Signal::Pipe),
+    ("ALRM", Signal::Alarm),
+    ("TERM", Signal::Term),
+    ("CHLD", Signal::Child),
+    ("CONT", Signal::Continue),
+    ("STOP", Signal::Stop),
+    ("TSTP", Signal::TSTP),
+    ("TTIN", Signal::TTIN),
+    ("TTOU", Signal::TTOU),
+    ("URG", Signal::Urgent),
+    ("XCPU", Signal::XCPU),
+    ("XFSZ", Signal::XFSZ),
+    ("VTALRM", Signal::VirtualAlarm),
+    ("PROF", Signal::Profiling),
+    ("WINCH", Signal::Winch),
+    ("IO", Signal::IO),
+    ("POLL", Signal::Poll),
+    ("PWR", Signal::Power),
+    ("SYS", Signal::Sys),
+];
+
+/// Parses a signal name such as `TERM`, `SIGTERM` or `term`.
+pub fn parse_signal(name: &str) -> Option<Signal> {
+    let name = name.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    SIGNAL_NAMES
+        .iter()
+        .find(|(signal_name, _)| *signal_name == name)
+        .map(|(_, signal)| *signal)
+}
+
+/// Short conventional name of `signal`, e.g. `TERM`.
+pub fn signal_name(signal: Signal) -> &'static str {
+    SIGNAL_NAMES
+        .iter()
+        .find(|(_, known)| *known == signal)
+        .map(|(name, _)| *name)
+        .expect("Every signal has a name")
+}
+
+/// Whether `signal` can be delivered on the current platform.
+pub fn is_signal_supported(signal: Signal) -> bool {
+    SUPPORTED_SIGNALS.contains(&signal)
+}
+
+/// Result of sending a signal to a single process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillOutcome {
+    Delivered,
+    PermissionDenied,
+    /// The process does not exist or exited before the signal was sent.
+    NotFound,
+    UnsupportedSignal,
+}
+
+impl fmt::Display for KillOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            KillOutcome::Delivered => "delivered",
+            KillOutcome::PermissionDenied => "permission denied",
+            KillOutcome::NotFound => "no such process",
+            KillOutcome::UnsupportedSignal => "signal not supported on this platform",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillReport {
+    pub pid: u32,
+    pub outcome: KillOutcome,
+}
+
+/// Sends `signal` to every PID in `pids` and reports what happened to each.
+/// PIDs that are not currently running are never signalled.
+pub fn send_signal(pids: &[u32], signal: Signal) -> Result<Vec<KillReport>> {
+    let mut system = get_system()?;
+    let targets: Vec<Pid> = pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    system.refresh_processes(ProcessesToUpdate::Some(&targets), true);
+
+    let mut reports = Vec::new();
+    for pid in targets {
+        let delivered = match system.process(pid) {
+            Some(process) => process.kill_with(signal),
+            None => {
+                reports.push(KillReport {
+                    pid: pid.as_u32(),
+                    outcome: KillOutcome::NotFound,
+                });
+                continue;
+            }
+        };
+        let outcome = match delivered {
+            None => KillOutcome::UnsupportedSignal,
+            Some(true) => KillOutcome::Delivered,
+            Some(false) => {
+                // kill(2) only fails with EPERM or ESRCH once the signal is
+                // known to be valid, so tell them apart by looking again.
+                system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+                match system.process(pid) {
+                    Some(_) => KillOutcome::PermissionDenied,
+                    None => KillOutcome::NotFound,
+                }
+            }
+        };
+        reports.push(KillReport {
+            pid: pid.as_u32(),
+            outcome,
+        });
+    }
+    Ok(reports)
+}
+
+/// Result of renicing a single process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReniceOutcome {
+    Reniced,
+    PermissionDenied,
+    /// The process does not exist or exited before it could be reniced.
+    NotFound,
+    Unsupported,
+}
+
+impl fmt::Display for ReniceOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ReniceOutcome::Reniced => "reniced",
+            ReniceOutcome::PermissionDenied => "permission denied",
+            ReniceOutcome::NotFound => "no such process",
+            ReniceOutcome::Unsupported => "renicing not supported on this platform",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReniceReport {
+    pub pid: u32,
+    pub outcome: ReniceOutcome,
+}
+
+/// Sets the nice value of every PID in `pids`, from -20 (highest priority)
+/// to 19 (lowest). Lowering it below the current value needs privileges.
+///
+/// Linux keeps a nice value per thread, so every thread of the process is
+/// reniced, not only the main one. Threads started while this runs can be
+/// missed.
+#[cfg(unix)]
+pub fn renice(pids: &[u32], nice: i32) -> Result<Vec<ReniceReport>> {
+    let mut reports = Vec::new();
+    for pid in pids {
+        let outcome = match set_nice(*pid, nice) {
+            Ok(()) => renice_threads(*pid, nice)?,
+            Err(err) => match err.raw_os_error() {
+                Some(libc::ESRCH) => ReniceOutcome::NotFound,
+                Some(libc::EPERM | libc::EACCES) => ReniceOutcome::PermissionDenied,
+                _ => return Err(err.into()),
+            },
+        };
+        reports.push(ReniceReport { pid: *pid, outcome });
+    }
+    Ok(reports)
+}
+
+#[cfg(unix)]
+fn set_nice(id: u32, nice: i32) -> std::io::Result<()> {
+    // SAFETY: setpriority only reads its integer arguments.
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, id as libc::id_t, nice) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+// Renices the threads of `pid` other than the main one, which the caller
+// already did.
+#[cfg(target_os = "linux")]
+fn renice_threads(pid: u32, nice: i32) -> Result<ReniceOutcome> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/task", pid)) else {
+        // The process exited in the meantime.
+        return Ok(ReniceOutcome::Reniced);
+    };
+    for entry in entries.flatten() {
+        let Some(tid) = entry.file_name().to_str().and_then(|tid| tid.parse().ok()) else {
+            continue;
+        };
+        if tid == pid {
+            continue;
+        }
+        if let Err(err) = set_nice(tid, nice) {
+            match err.raw_os_error() {
+                // The thread exited since the listing.
+                Some(libc::ESRCH) => {}
+                Some(libc::EPERM | libc::EACCES) => return Ok(ReniceOutcome::PermissionDenied),
+                _ => return Err(err.into()),
+            }
+        }
+    }
+    Ok(ReniceOutcome::Reniced)
+}
+
+// Elsewhere the nice value belongs to the process as a whole.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn renice_threads(_pid: u32, _nice: i32) -> Result<ReniceOutcome> {
+    Ok(ReniceOutcome::Reniced)
+}
+
+#[cfg(not(unix))]
+pub fn renice(pids: &[u32], _nice: i32) -> Result<Vec<ReniceReport>> {
+    Ok(pids
+        .iter()
+        .map(|pid| ReniceReport {
+            pid: *pid,
+            outcome: ReniceOutcome::Unsupported,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KillOutcome, ReniceOutcome, parse_signal, signal_name};
+    use sysinfo::Signal;
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!(parse_signal("TERM"), Some(Signal::Term));
+        assert_eq!(parse_signal("SIGKILL"), Some(Signal::Kill));
+        assert_eq!(parse_signal("hup"), Some(Signal::Hangup));
+        assert_eq!(parse_signal("sigusr1"), Some(Signal::User1));
+        assert_eq!(parse_signal("NOPE"), None);
+        assert_eq!(parse_signal("SIG"), None);
+    }
+
+    #[test]
+    fn test_signal_name() {
+        for signal in sysinfo::SUPPORTED_SIGNALS {
+            assert_eq!(parse_signal(signal_name(*signal)), Some(*signal));
+        }
+    }
+
+    #[test]
+    fn test_send_signal() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("Failed to spawn sleep process");
+        let pid = child.id();
+
+        let reports = super::send_signal(&[pid], Signal::Term).unwrap();
+        let _ = child.wait();
+
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].pid, pid);
+        assert_eq!(reports[0].outcome, KillOutcome::Delivered);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_renice() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("Failed to spawn sleep process");
+        let pid = child.id();
+
+        let reports = super::renice(&[pid], 19).unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert_eq!(reports[0].outcome, ReniceOutcome::Reniced);
+        let reports = super::renice(&[4_194_305], 19).unwrap();
+        assert_eq!(reports[0].outcome, ReniceOutcome::NotFound);
+    }
+
+    // Does nothing in a normal test run. test_renice_threads runs it in a
+    // child process, where it parks a few threads for the test to renice.
+    #[test]
+    fn renice_threads_helper() {
+        if std::env::var_os("PROC_HUNTER_RENICE_HELPER").is_none() {
+            return;
+        }
+        for _ in 0..3 {
+            std::thread::spawn(|| std::thread::sleep(std::time::Duration::from_secs(30)));
+        }
+        std::thread::sleep(std::time::Duration::from_secs(30));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_renice_threads() {
+        let test_bin = std::env::current_exe().expect("Failed to get test executable path");
+        let mut child = std::process::Command::new(test_bin)
+            .args([
+                "--exact",
+                "models_fn_packer::signal_helper::packer::tests::renice_threads_helper",
+            ])
+            .env("PROC_HUNTER_RENICE_HELPER", "1")
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .expect("Failed to spawn helper process");
+        let pid = child.id();
+        let task = format!("/proc/{}/task", pid);
+        let tids = || -> Vec<String> {
+            std::fs::read_dir(&task)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        // The main thread, the test harness thread and the parked threads.
+        let started = std::time::Instant::now();
+        while tids().len() < 4 && started.elapsed() < std::time::Duration::from_secs(10) {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        let reports = super::renice(&[pid], 19).unwrap();
+        let nice_values: Vec<i32> = tids()
+            .iter()
+            .map(|tid| {
+                let stat = std::fs::read_to_string(format!("{}/{}/stat", task, tid)).unwrap();
+                // Field 19, counting from the state after the command name.
+                let fields: Vec<&str> = stat[stat.rfind(')').unwrap() + 1..]
+                    .split_whitespace()
+                    .collect();
+                fields[16].parse().unwrap()
+            })
+            .collect();
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert_eq!(reports[0].outcome, ReniceOutcome::Reniced);
+        assert!(nice_values.len() >= 4, "Helper threads should be running");
+        assert!(nice_values.iter().all(|nice| *nice == 19));
+    }
+
+    #[test]
+    fn test_send_signal_to_missing_process() {
+        // Above the largest pid_max Linux allows.
+        let pid = 4_194_305;
+
+        let reports = super::send_signal(&[pid], Signal::Term).unwrap();
+
+        assert_eq!(reports[0].outcome, KillOutcome::NotFound);
+    }
+}
@@ -0,0 +1,9 @@
+mod packer;
+
+#[cfg(test)]
+pub(crate) use packer::FixtureDir;
+pub use packer::FixtureSource;
+pub use packer::LiveSource;
+pub use packer::Snapshot;
+pub use packer::SystemSource;
+pub use packer::now_ms;
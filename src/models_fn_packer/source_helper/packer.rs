@@ -0,0 +1,237 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::models_fn_packer::battery_helper::{BatteryDetails, BatteryInfo, get_batteries};
+use crate::models_fn_packer::sys_info_helper::{CpuInfo, MemoryInfo, ProcessSummary};
+use crate::models_fn_packer::sys_info_helper::{get_cpu_info, get_memory_info, sample_cpu};
+use crate::models_fn_packer::top_helper::{ProcessRow, ProcessTable};
+use crate::{Error, Result};
+
+/// Everything a source reports at one point in time.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub cpu: CpuInfo,
+    pub memory: MemoryInfo,
+    pub processes: Vec<ProcessRow>,
+    /// Empty on machines without a battery.
+    pub batteries: Vec<BatteryDetails>,
+}
+
+/// Where system information comes from.
+///
+/// The reporting code only needs these methods, so it works the same on
+/// the running machine ([`LiveSource`]) and on canned data
+/// ([`FixtureSource`]).
+pub trait SystemSource {
+    /// CPU usage since the previous call.
+    fn cpu(&mut self) -> Result<CpuInfo>;
+
+    fn memory(&mut self) -> Result<MemoryInfo>;
+
+    /// Every process, threads excluded, in no particular order.
+    fn processes(&mut self) -> Result<Vec<ProcessRow>>;
+
+    /// Every battery, or [`Error::NoBatteries`].
+    fn batteries(&mut self) -> Result<Vec<BatteryDetails>>;
+
+    /// CPU usage measured over `window`, raised to
+    /// `sysinfo::MINIMUM_CPU_UPDATE_INTERVAL` if shorter.
+    fn sample_cpu(&mut self, window: Duration) -> Result<CpuInfo> {
+        self.cpu()?;
+        std::thread::sleep(window.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL));
+        self.cpu()
+    }
+
+    fn process_summary(&mut self) -> Result<ProcessSummary> {
+        Ok(ProcessSummary::from_rows(&self.processes()?))
+    }
+
+    fn battery_info(&mut self) -> Result<BatteryInfo> {
+        Ok(BatteryInfo::from_batteries(&self.batteries()?))
+    }
+
+    /// Reads everything at once. Battery failures leave `batteries` empty
+    /// instead of failing the whole snapshot.
+    fn snapshot(&mut self) -> Result<Snapshot> {
+        Ok(Snapshot {
+            timestamp_ms: now_ms(),
+            cpu: self.cpu()?,
+            memory: self.memory()?,
+            processes: self.processes()?,
+            batteries: self.batteries().unwrap_or_default(),
+        })
+    }
+}
+
+/// Milliseconds since the Unix epoch, 0 if the clock is before it.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// The running machine, through `sysinfo` and the battery backend.
+#[derive(Default)]
+pub struct LiveSource {
+    table: ProcessTable,
+}
+
+impl LiveSource {
+    pub fn new() -> Self {
+        LiveSource {
+            table: ProcessTable::new(),
+        }
+    }
+}
+
+impl SystemSource for LiveSource {
+    fn cpu(&mut self) -> Result<CpuInfo> {
+        get_cpu_info()
+    }
+
+    fn sample_cpu(&mut self, window: Duration) -> Result<CpuInfo> {
+        sample_cpu(window)
+    }
+
+    fn memory(&mut self) -> Result<MemoryInfo> {
+        get_memory_info()
+    }
+
+    fn processes(&mut self) -> Result<Vec<ProcessRow>> {
+        self.table.sample(None)
+    }
+
+    fn batteries(&mut self) -> Result<Vec<BatteryDetails>> {
+        get_batteries()
+    }
+}
+
+/// Serves a fixed [`Snapshot`], for tests and recorded data. Change
+/// `snapshot` between calls to simulate the system evolving.
+#[derive(Debug, Clone, Default)]
+pub struct FixtureSource {
+    pub snapshot: Snapshot,
+}
+
+impl FixtureSource {
+    pub fn new(snapshot: Snapshot) -> Self {
+        FixtureSource { snapshot }
+    }
+}
+
+impl SystemSource for FixtureSource {
+    fn cpu(&mut self) -> Result<CpuInfo> {
+        Ok(self.snapshot.cpu.clone())
+    }
+
+    // Canned data does not change over the window, so there is nothing to
+    // wait for.
+    fn sample_cpu(&mut self, _window: Duration) -> Result<CpuInfo> {
+        self.cpu()
+    }
+
+    fn memory(&mut self) -> Result<MemoryInfo> {
+        Ok(self.snapshot.memory)
+    }
+
+    fn processes(&mut self) -> Result<Vec<ProcessRow>> {
+        Ok(self.snapshot.processes.clone())
+    }
+
+    fn batteries(&mut self) -> Result<Vec<BatteryDetails>> {
+        if self.snapshot.batteries.is_empty() {
+            return Err(Error::NoBatteries);
+        }
+        Ok(self.snapshot.batteries.clone())
+    }
+
+    fn snapshot(&mut self) -> Result<Snapshot> {
+        Ok(self.snapshot.clone())
+    }
+}
+
+/// Scratch directory under the system temp directory, removed on drop.
+#[cfg(test)]
+pub(crate) struct FixtureDir {
+    pub root: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl FixtureDir {
+    /// `name` must be unique among the tests, which run in parallel.
+    pub fn new(name: &str) -> Self {
+        let root =
+            std::env::temp_dir().join(format!("proc_hunter_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        FixtureDir { root }
+    }
+
+    /// Writes `content` to `path` relative to the root, creating parent
+    /// directories as needed.
+    pub fn write(&self, path: &str, content: &str) {
+        let path = self.root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+}
+
+#[cfg(test)]
+impl Drop for FixtureDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{FixtureSource, LiveSource, Snapshot, SystemSource};
+    use crate::Error;
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    fn row(pid: u32, ppid: Option<u32>) -> ProcessRow {
+        ProcessRow {
+            ppid,
+            memory: 1024,
+            ..ProcessRow::fixture(pid, &format!("p{}", pid))
+        }
+    }
+
+    #[test]
+    fn test_fixture_source() {
+        let mut source = FixtureSource::new(Snapshot {
+            timestamp_ms: 42,
+            processes: vec![row(1, None), row(2, Some(0)), row(3, Some(1))],
+            ..Snapshot::default()
+        });
+
+        let summary = source.process_summary().unwrap();
+        assert_eq!(summary.process_count, 3);
+        assert_eq!(summary.root_process_count, 2);
+        assert!(matches!(source.battery_info(), Err(Error::NoBatteries)));
+        assert_eq!(source.snapshot().unwrap().timestamp_ms, 42);
+
+        source.snapshot.processes.pop();
+        assert_eq!(source.processes().unwrap().len(), 2);
+
+        let started = std::time::Instant::now();
+        source.sample_cpu(Duration::from_secs(10)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_live_source() {
+        let mut source = LiveSource::new();
+        let snapshot = source.snapshot().unwrap();
+
+        assert!(snapshot.timestamp_ms > 0);
+        assert!(snapshot.memory.total_memory > 0);
+        assert!(!snapshot.cpu.cores.is_empty());
+        let own_pid = std::process::id();
+        assert!(snapshot.processes.iter().any(|row| row.pid == own_pid));
+    }
+}
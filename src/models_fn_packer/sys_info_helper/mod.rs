@@ -1,5 +1,19 @@
 mod packer;
+pub use packer::CoreInfo;
+pub use packer::CpuInfo;
+pub use packer::MemoryInfo;
+pub use packer::ProcessSummary;
+#[allow(deprecated)]
 pub use packer::get_cpu_brief_info;
+#[allow(deprecated)]
 pub use packer::get_cpu_brief_info_with_1_ms_sleep;
+pub use packer::get_cpu_info;
+#[allow(deprecated)]
 pub use packer::get_memory_brief_info;
+pub use packer::get_memory_info;
+#[allow(deprecated)]
 pub use packer::get_processes_brief_info;
+pub use packer::get_processes_summary;
+pub use packer::get_system;
+pub use packer::refresh_processes_with_details;
+pub use packer::sample_cpu;
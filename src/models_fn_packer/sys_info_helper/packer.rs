@@ -1,120 +1,286 @@
 use std::sync::{Mutex, MutexGuard, OnceLock};
-use sysinfo::{ProcessesToUpdate, System};
-
-// before calling this method, make sure youhave called get_system() at least once
-pub fn get_cpu_brief_info() -> (usize, usize, f32, Vec<(String, String, u64, f32)>) {
-    let system = refresh_cpu();
-    let physical_cpu_core_num = sysinfo::System::physical_core_count().unwrap_or(0);
-    let global_cpu_usage = system.global_cpu_usage();
-    let cpus = system.cpus();
-    let cpu_num = cpus.len();
-    if cpu_num == 0 {
-        return (physical_cpu_core_num, 0, global_cpu_usage, Vec::new());
-    }
-    let mut cpus_info = Vec::new();
-    for cpu in cpus {
-        let cpu_usage = cpu.cpu_usage();
-        let cpu_frequency = cpu.frequency();
-        let cpu_brand = cpu.brand().to_string();
-        let cpu_name = cpu.name().to_string();
-        cpus_info.push((cpu_brand, cpu_name, cpu_frequency, cpu_usage));
-    }
-    return (physical_cpu_core_num, cpu_num, global_cpu_usage, cpus_info);
+use std::time::Duration;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::models_fn_packer::top_helper::ProcessRow;
+use crate::{Error, Result};
+
+// (cpu_brand, cpu_name, cpu_frequency, cpu_usage)
+type CpuCoreBriefInfo = (String, String, u64, f32);
+
+/// Snapshot of the CPU as a whole.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuInfo {
+    /// Number of physical cores, `None` if the platform does not report it.
+    pub physical_core_count: Option<usize>,
+    /// Usage of all cores combined, in percent (0-100).
+    pub global_usage: f32,
+    /// One entry per logical core.
+    pub cores: Vec<CoreInfo>,
 }
 
-pub fn get_cpu_brief_info_with_1_ms_sleep() -> (usize, usize, f32, Vec<(String, String, u64, f32)>)
-{
-    let system = refresh_cpu();
-    // Sleep for 1 ms to allow CPU usage to be updated
-    std::thread::sleep(std::time::Duration::from_millis(1));
-    // Refresh the system again to get updated CPU usage
-    let physical_cpu_core_num = sysinfo::System::physical_core_count().unwrap_or(0);
-    let global_cpu_usage = system.global_cpu_usage();
-    let cpus = system.cpus();
-    let cpu_num = cpus.len();
-    if cpu_num == 0 {
-        return (physical_cpu_core_num, 0, global_cpu_usage, Vec::new());
-    }
-    let mut cpus_info = Vec::new();
-    for cpu in cpus {
-        let cpu_usage = cpu.cpu_usage();
-        let cpu_frequency = cpu.frequency();
-        let cpu_brand = cpu.brand().to_string();
-        let cpu_name = cpu.name().to_string();
-        cpus_info.push((cpu_brand, cpu_name, cpu_frequency, cpu_usage));
-    }
-    return (physical_cpu_core_num, cpu_num, global_cpu_usage, cpus_info);
+impl CpuInfo {
+    pub fn logical_core_count(&self) -> usize {
+        self.cores.len()
+    }
 }
 
-pub fn get_memory_brief_info() -> (u64, u64, u64, u64, u64, u64) {
-    let system = refresh_memory();
-    let total_memory = system.total_memory();
-    let free_memory = system.free_memory();
-    let used_memory = system.used_memory();
-    let total_swap = system.total_swap();
-    let free_swap = system.free_swap();
-    let used_swap = system.used_swap();
-    return (
-        total_memory,
-        free_memory,
-        used_memory,
-        total_swap,
-        free_swap,
-        used_swap,
-    );
+/// Snapshot of a single logical core.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreInfo {
+    /// Name given by the OS, e.g. `cpu0`.
+    pub name: String,
+    /// Marketing name of the processor.
+    pub brand: String,
+    pub vendor_id: String,
+    /// Current frequency in MHz.
+    pub frequency: u64,
+    /// Usage in percent (0-100).
+    pub usage: f32,
 }
 
-pub fn get_processes_brief_info() -> (usize, usize) {
-    let system = refresh_processes();
+/// RAM and swap figures, all in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryInfo {
+    pub total_memory: u64,
+    /// Memory not used for anything, not even caches.
+    pub free_memory: u64,
+    /// Memory that can be handed out to new allocations, caches included.
+    pub available_memory: u64,
+    pub used_memory: u64,
+    pub total_swap: u64,
+    pub free_swap: u64,
+    pub used_swap: u64,
+}
+
+/// Process counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessSummary {
+    pub process_count: usize,
+    /// Processes without a parent, or whose parent is PID 0.
+    pub root_process_count: usize,
+}
+
+impl ProcessSummary {
+    pub fn from_rows(rows: &[ProcessRow]) -> Self {
+        ProcessSummary {
+            process_count: rows.len(),
+            root_process_count: rows
+                .iter()
+                .filter(|row| row.ppid.is_none_or(|ppid| ppid == 0))
+                .count(),
+        }
+    }
+}
+
+/// CPU usage accumulated since the previous CPU refresh of the shared
+/// system, whenever that was. Use [`sample_cpu`] for a defined window.
+pub fn get_cpu_info() -> Result<CpuInfo> {
+    let system = refresh_cpu()?;
+    Ok(cpu_info_from(&system))
+}
+
+/// Measures CPU usage over `window`, which is raised to
+/// `sysinfo::MINIMUM_CPU_UPDATE_INTERVAL` if shorter.
+///
+/// The shared system stays locked for the whole window so that no other
+/// refresh can cut it short.
+pub fn sample_cpu(window: Duration) -> Result<CpuInfo> {
+    let window = window.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    let mut system = get_system()?;
+    system.refresh_cpu_all();
+    std::thread::sleep(window);
+    system.refresh_cpu_all();
+    Ok(cpu_info_from(&system))
+}
+
+fn cpu_info_from(system: &System) -> CpuInfo {
+    let cores = system
+        .cpus()
+        .iter()
+        .map(|cpu| CoreInfo {
+            name: cpu.name().to_string(),
+            brand: cpu.brand().to_string(),
+            vendor_id: cpu.vendor_id().to_string(),
+            frequency: cpu.frequency(),
+            usage: cpu.cpu_usage(),
+        })
+        .collect();
+    CpuInfo {
+        physical_core_count: sysinfo::System::physical_core_count(),
+        global_usage: system.global_cpu_usage(),
+        cores,
+    }
+}
+
+pub fn get_memory_info() -> Result<MemoryInfo> {
+    let system = refresh_memory()?;
+    Ok(memory_info_from(&system))
+}
+
+fn memory_info_from(system: &System) -> MemoryInfo {
+    MemoryInfo {
+        total_memory: system.total_memory(),
+        free_memory: system.free_memory(),
+        available_memory: system.available_memory(),
+        used_memory: system.used_memory(),
+        total_swap: system.total_swap(),
+        free_swap: system.free_swap(),
+        used_swap: system.used_swap(),
+    }
+}
+
+pub fn get_processes_summary() -> Result<ProcessSummary> {
+    let system = refresh_processes()?;
+    Ok(processes_summary_from(&system))
+}
+
+fn processes_summary_from(system: &System) -> ProcessSummary {
     let processes = system.processes();
-    let processes_num = processes.len();
-    let mut root = 0;
+    let mut root_process_count = 0;
     for process in processes.values() {
         match process.parent() {
             Some(parent) => {
                 if parent == sysinfo::Pid::from_u32(0) {
-                    root += 1;
+                    root_process_count += 1;
                 }
             }
             None => {
-                root += 1;
+                root_process_count += 1;
             }
         }
     }
-    return (processes_num, root);
+    ProcessSummary {
+        process_count: processes.len(),
+        root_process_count,
+    }
+}
+
+fn cpu_info_to_tuple(cpu: CpuInfo) -> (usize, usize, f32, Vec<CpuCoreBriefInfo>) {
+    let cpu_num = cpu.logical_core_count();
+    let cpus_info = cpu
+        .cores
+        .into_iter()
+        .map(|core| (core.brand, core.name, core.frequency, core.usage))
+        .collect();
+    (
+        cpu.physical_core_count.unwrap_or(0),
+        cpu_num,
+        cpu.global_usage,
+        cpus_info,
+    )
+}
+
+// The deprecated shims below keep their original signatures, which leave no
+// room for an error. A poisoned lock only means another thread panicked
+// while holding it, and every call refreshes what it reads anyway, so they
+// take the lock back instead of panicking.
+fn lock_system_unpoisoned() -> MutexGuard<'static, System> {
+    SYSTEM
+        .get_or_init(|| Mutex::new(System::new_all()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn cpu_brief_info() -> (usize, usize, f32, Vec<CpuCoreBriefInfo>) {
+    let mut system = lock_system_unpoisoned();
+    system.refresh_cpu_all();
+    cpu_info_to_tuple(cpu_info_from(&system))
+}
+
+#[deprecated(note = "use get_cpu_info() instead")]
+pub fn get_cpu_brief_info() -> (usize, usize, f32, Vec<CpuCoreBriefInfo>) {
+    cpu_brief_info()
+}
+
+#[deprecated(note = "use sample_cpu() instead, the sleep never had any effect")]
+pub fn get_cpu_brief_info_with_1_ms_sleep() -> (usize, usize, f32, Vec<CpuCoreBriefInfo>) {
+    cpu_brief_info()
 }
+
+#[deprecated(note = "use get_memory_info() instead")]
+pub fn get_memory_brief_info() -> (u64, u64, u64, u64, u64, u64) {
+    let mut system = lock_system_unpoisoned();
+    system.refresh_memory();
+    let memory = memory_info_from(&system);
+    (
+        memory.total_memory,
+        memory.free_memory,
+        memory.used_memory,
+        memory.total_swap,
+        memory.free_swap,
+        memory.used_swap,
+    )
+}
+
+#[deprecated(note = "use get_processes_summary() instead")]
+pub fn get_processes_brief_info() -> (usize, usize) {
+    let mut system = lock_system_unpoisoned();
+    system.refresh_processes(ProcessesToUpdate::All, false);
+    let summary = processes_summary_from(&system);
+    (summary.process_count, summary.root_process_count)
+}
+
 static SYSTEM: OnceLock<Mutex<System>> = OnceLock::new();
 
-pub fn get_system() -> MutexGuard<'static, System> {
+pub fn get_system() -> Result<MutexGuard<'static, System>> {
     let system = SYSTEM.get_or_init(|| Mutex::new(System::new_all()));
 
-    system.lock().expect("System mutex was poisoned")
+    system.lock().map_err(|_| Error::LockPoisoned)
 }
 
-pub fn refresh_processes() -> MutexGuard<'static, System> {
-    let mut system = get_system();
+pub fn refresh_processes() -> Result<MutexGuard<'static, System>> {
+    let mut system = get_system()?;
     system.refresh_processes(ProcessesToUpdate::All, false);
-    system
+    Ok(system)
 }
 
-pub fn refresh_memory() -> MutexGuard<'static, System> {
-    let mut system = get_system();
+// Same as refresh_processes(), but also loads the command line and owner of
+// newly spawned processes and drops the ones that have exited.
+pub fn refresh_processes_with_details() -> Result<MutexGuard<'static, System>> {
+    let mut system = get_system()?;
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_memory()
+            .with_cpu()
+            .with_disk_usage()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_user(UpdateKind::OnlyIfNotSet)
+            .with_tasks(),
+    );
+    Ok(system)
+}
+
+pub fn refresh_memory() -> Result<MutexGuard<'static, System>> {
+    let mut system = get_system()?;
     system.refresh_memory();
-    system
+    Ok(system)
 }
 
-pub fn refresh_cpu() -> MutexGuard<'static, System> {
-    let mut system = get_system();
+pub fn refresh_cpu() -> Result<MutexGuard<'static, System>> {
+    let mut system = get_system()?;
     system.refresh_cpu_all();
-    system
+    Ok(system)
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::zombie_processes,
+    clippy::needless_borrows_for_generic_args,
+    clippy::redundant_pattern_matching
+)]
 mod tests {
 
     #[test]
     fn test_available_memory() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let available_memory = system.available_memory();
 
         println!("Available memory: {} bytes", available_memory);
@@ -135,7 +301,7 @@ mod tests {
 
     #[test]
     fn test_cgroup_limits() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         system.cgroup_limits();
     }
 
@@ -148,7 +314,7 @@ mod tests {
 
     #[test]
     fn test_cpus() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let cpus = system.cpus();
         assert!(!cpus.is_empty(), "CPUs should not be empty");
     }
@@ -169,7 +335,7 @@ mod tests {
 
     #[test]
     fn test_free_memory() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let free_memory = system.free_memory();
 
         println!("Free memory: {} bytes", free_memory);
@@ -179,7 +345,7 @@ mod tests {
 
     #[test]
     fn test_free_swap() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let free_swap = system.free_swap();
 
         println!("Free swap: {} bytes", free_swap);
@@ -189,7 +355,7 @@ mod tests {
 
     #[test]
     fn test_global_cpu_usage() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let global_cpu_usage = system.global_cpu_usage();
 
         println!("Global CPU usage: {:.2}%", global_cpu_usage * 1.0);
@@ -253,7 +419,7 @@ mod tests {
 
     #[test]
     fn test_processes() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let processes = system.processes();
 
         assert!(!processes.is_empty(), "Processes should not be empty");
@@ -261,7 +427,7 @@ mod tests {
 
     #[test]
     fn test_process() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let processes = system.processes();
         let mut success_count = 0;
         for process in processes.values() {
@@ -275,7 +441,7 @@ mod tests {
 
     #[test]
     fn test_total_memory() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let total_memory = system.total_memory();
 
         println!("Total memory: {} bytes", total_memory);
@@ -285,7 +451,7 @@ mod tests {
 
     #[test]
     fn test_total_swap() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let total_swap = system.total_swap();
 
         println!("Total swap: {} bytes", total_swap);
@@ -306,7 +472,7 @@ mod tests {
 
     #[test]
     fn test_used_memory() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let used_memory = system.used_memory();
 
         println!("Used memory: {} bytes", used_memory);
@@ -316,7 +482,7 @@ mod tests {
 
     #[test]
     fn test_used_swap() {
-        let system = super::get_system();
+        let system = super::get_system().unwrap();
         let used_swap = system.used_swap();
 
         println!("Used swap: {} bytes", used_swap);
@@ -327,7 +493,7 @@ mod tests {
     #[test]
     fn test_system_singleton() {
         // Initialize the OnceLock first
-        let _system = super::get_system();
+        let _system = super::get_system().unwrap();
 
         // Get the address of the Mutex itself, not the MutexGuard
         let system1_ptr = super::SYSTEM.get().unwrap() as *const _ as usize;
@@ -342,15 +508,29 @@ mod tests {
 
     #[test]
     fn test_refresh_processes() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         assert!(!processes.is_empty(), "Processes should not be empty");
     }
 
+    #[test]
+    fn test_refresh_processes_with_details() {
+        let system = super::refresh_processes_with_details().unwrap();
+        let own_pid = sysinfo::Pid::from_u32(std::process::id());
+        let own_process = system
+            .process(own_pid)
+            .expect("Own process should be listed");
+
+        assert!(
+            !own_process.cmd().is_empty(),
+            "Command line should be loaded"
+        );
+    }
+
     #[test]
     fn test_refresh_memory() {
-        let system = super::refresh_memory();
+        let system = super::refresh_memory().unwrap();
         let total_memory = system.total_memory();
 
         println!("Total memory: {} bytes", total_memory);
@@ -361,15 +541,90 @@ mod tests {
     #[test]
     fn test_refresh_cpu() {
         {
-            let system = super::get_system();
+            let system = super::get_system().unwrap();
             assert!(!system.cpus().is_empty(), "CPUs should not be empty");
         }
-        let system = super::refresh_cpu();
+        let system = super::refresh_cpu().unwrap();
         let cpu_usage = system.global_cpu_usage();
 
         println!("Global CPU usage: {:.2}%", cpu_usage * 1.0);
     }
 
+    #[test]
+    fn test_get_cpu_info() {
+        let cpu = super::get_cpu_info().unwrap();
+
+        assert!(cpu.logical_core_count() > 0, "CPUs should not be empty");
+        assert!((0.0..=100.0).contains(&cpu.global_usage));
+        for core in &cpu.cores {
+            assert!(!core.name.is_empty(), "Core name should not be empty");
+        }
+    }
+
+    #[test]
+    fn test_sample_cpu() {
+        let busy = std::thread::spawn(|| {
+            let start = std::time::Instant::now();
+            let mut counter: u64 = 0;
+            while start.elapsed() < std::time::Duration::from_millis(400) {
+                counter = counter.wrapping_add(1);
+                std::hint::black_box(counter);
+            }
+        });
+
+        let start = std::time::Instant::now();
+        let cpu = super::sample_cpu(std::time::Duration::from_millis(1)).unwrap();
+        let elapsed = start.elapsed();
+        busy.join().unwrap();
+
+        assert!(
+            elapsed >= sysinfo::MINIMUM_CPU_UPDATE_INTERVAL,
+            "Window should be raised to the minimum update interval"
+        );
+        assert!(cpu.logical_core_count() > 0, "CPUs should not be empty");
+        assert!(
+            cpu.global_usage > 0.0,
+            "A busy thread should show up in the sampled window"
+        );
+        assert!(cpu.global_usage <= 100.0);
+    }
+
+    #[test]
+    fn test_get_memory_info() {
+        let memory = super::get_memory_info().unwrap();
+
+        assert!(memory.total_memory > 0, "Total memory should not be zero");
+        assert!(memory.used_memory <= memory.total_memory);
+        assert!(memory.used_swap <= memory.total_swap);
+    }
+
+    #[test]
+    fn test_get_processes_summary() {
+        let summary = super::get_processes_summary().unwrap();
+
+        assert!(summary.process_count > 0, "Processes should not be empty");
+        assert!(summary.root_process_count <= summary.process_count);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_brief_info() {
+        let (physical_num, cpu_num, _, cpus) = super::get_cpu_brief_info();
+        assert_eq!(cpu_num, cpus.len());
+        assert_eq!(
+            physical_num,
+            sysinfo::System::physical_core_count().unwrap_or(0)
+        );
+
+        let (total_memory, _, used_memory, total_swap, _, used_swap) =
+            super::get_memory_brief_info();
+        assert!(used_memory <= total_memory);
+        assert!(used_swap <= total_swap);
+
+        let (processes_num, root) = super::get_processes_brief_info();
+        assert!(root <= processes_num);
+    }
+
     fn bytes_to_mb(bytes: u64) -> f64 {
         (bytes as f64) / 1024.0 / 1024.0
     }
@@ -380,7 +635,7 @@ mod tests {
 
     #[test]
     fn test_cpu_brand() {
-        let system = super::refresh_cpu();
+        let system = super::refresh_cpu().unwrap();
         let cpu_brand = system.cpus()[0].brand();
 
         println!("CPU brand: {:?}", cpu_brand);
@@ -389,12 +644,12 @@ mod tests {
     #[test]
     fn test_cpu_usage() {
         {
-            let system = super::refresh_cpu();
+            let system = super::refresh_cpu().unwrap();
             assert!(!system.cpus().is_empty(), "CPUs should not be empty");
         }
         // wait a bit();
         std::thread::sleep(std::time::Duration::from_secs(1));
-        let system = super::refresh_cpu();
+        let system = super::refresh_cpu().unwrap();
         let cpu_usage = system.cpus()[0].cpu_usage();
 
         println!("CPU usage: {:.2}%", cpu_usage);
@@ -403,10 +658,10 @@ mod tests {
     #[test]
     fn test_cpu_frequency() {
         {
-            let system = super::refresh_cpu();
+            let system = super::refresh_cpu().unwrap();
             assert!(!system.cpus().is_empty(), "CPUs should not be empty");
         }
-        let system = super::refresh_cpu();
+        let system = super::refresh_cpu().unwrap();
         let cpu_frequency = system.cpus()[0].frequency();
 
         println!("CPU frequency: {} MHz", cpu_frequency);
@@ -414,7 +669,7 @@ mod tests {
 
     #[test]
     fn test_cpu_name() {
-        let system = super::refresh_cpu();
+        let system = super::refresh_cpu().unwrap();
         let cpu_name = system.cpus()[0].name();
 
         println!("CPU name: {:?}", cpu_name);
@@ -422,7 +677,7 @@ mod tests {
 
     #[test]
     fn test_cpu_vendor_id() {
-        let system = super::refresh_cpu();
+        let system = super::refresh_cpu().unwrap();
         let cpu_vendor_id = system.cpus()[0].vendor_id();
 
         println!("CPU vendor ID: {:?}", cpu_vendor_id);
@@ -430,7 +685,7 @@ mod tests {
 
     #[test]
     fn test_process_accumulated_cpu_time() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values() {
@@ -440,7 +695,7 @@ mod tests {
 
     #[test]
     fn test_process_cmd() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -452,13 +707,13 @@ mod tests {
     #[test]
     fn test_process_cpu_usage() {
         {
-            let system = super::refresh_processes();
+            let system = super::refresh_processes().unwrap();
             assert!(
                 !system.processes().is_empty(),
                 "Processes should not be empty"
             );
         }
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -469,7 +724,7 @@ mod tests {
 
     #[test]
     fn test_process_cwd() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -480,7 +735,7 @@ mod tests {
 
     #[test]
     fn test_process_disk_usage() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -491,7 +746,7 @@ mod tests {
 
     #[test]
     fn test_process_effective_group_id() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -502,7 +757,7 @@ mod tests {
 
     #[test]
     fn test_process_effective_user_id() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -513,7 +768,7 @@ mod tests {
 
     #[test]
     fn test_process_environ() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -523,7 +778,7 @@ mod tests {
     }
     #[test]
     fn test_process_exe() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -534,7 +789,7 @@ mod tests {
 
     #[test]
     fn test_process_exists() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -545,7 +800,7 @@ mod tests {
 
     #[test]
     fn test_process_group_id() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -580,7 +835,7 @@ mod tests {
         std::thread::sleep(std::time::Duration::from_secs(1));
 
         // Attempt to kill the process
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         if let Some(process) = system.process(pid) {
             let result = process.kill();
             println!("Kill result: {:?}", result);
@@ -609,7 +864,7 @@ mod tests {
         std::thread::sleep(std::time::Duration::from_secs(1));
 
         // Attempt to kill the process
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         if let Some(process) = system.process(pid) {
             let result = process.kill_with(sysinfo::Signal::Kill).unwrap();
             println!("Kill result: {:?}", result);
@@ -622,7 +877,7 @@ mod tests {
 
     #[test]
     fn test_process_memory() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -633,7 +888,7 @@ mod tests {
 
     #[test]
     fn test_process_name() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -644,7 +899,7 @@ mod tests {
 
     #[test]
     fn test_process_open_files() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -655,7 +910,7 @@ mod tests {
 
     #[test]
     fn test_process_open_files_limit() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -666,7 +921,7 @@ mod tests {
 
     #[test]
     fn test_process_parent() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -677,7 +932,7 @@ mod tests {
 
     #[test]
     fn test_process_pid() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -688,7 +943,7 @@ mod tests {
 
     #[test]
     fn test_process_root() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -699,7 +954,7 @@ mod tests {
 
     #[test]
     fn test_process_run_time() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -710,7 +965,7 @@ mod tests {
 
     #[test]
     fn test_process_session_id() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -721,7 +976,7 @@ mod tests {
 
     #[test]
     fn test_process_start_time() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -732,7 +987,7 @@ mod tests {
 
     #[test]
     fn test_process_status() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -743,7 +998,7 @@ mod tests {
 
     #[test]
     fn test_process_tasks() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -754,7 +1009,7 @@ mod tests {
 
     #[test]
     fn test_process_thread_kind() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -765,7 +1020,7 @@ mod tests {
 
     #[test]
     fn test_process_user_id() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -776,7 +1031,7 @@ mod tests {
 
     #[test]
     fn test_process_virtual_memory() {
-        let system = super::refresh_processes();
+        let system = super::refresh_processes().unwrap();
         let processes = system.processes();
 
         for process in processes.values().take(5) {
@@ -0,0 +1,6 @@
+mod packer;
+
+pub use packer::ProcessRow;
+pub use packer::ProcessTable;
+pub use packer::SortKey;
+pub use packer::sort_process_rows;
@@ -0,0 +1,206 @@
+use std::cmp::Ordering;
+
+use sysinfo::{ThreadKind, Users};
+
+use crate::Result;
+use crate::models_fn_packer::hunt_helper::ProcessMatcher;
+use crate::models_fn_packer::sys_info_helper::refresh_processes_with_details;
+
+/// One line of a process listing.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessRow {
+    pub pid: u32,
+    /// Parent process ID, `None` for root processes.
+    pub ppid: Option<u32>,
+    /// Name of the owning user, `None` if it cannot be resolved.
+    pub user: Option<String>,
+    /// CPU usage in percent since the previous sample. Can exceed 100 on
+    /// multi-core machines.
+    pub cpu_usage: f32,
+    /// Resident set size in bytes.
+    pub memory: u64,
+    /// Scheduler state as reported by the OS, e.g. `Sleeping`.
+    pub state: String,
+    /// Number of threads, the main one included.
+    pub threads: usize,
+    pub name: String,
+    /// Command line, or `[name]` when the process has no arguments.
+    pub command: String,
+}
+
+#[cfg(test)]
+impl ProcessRow {
+    /// A sleeping, single-threaded process called `name`, which is also its
+    /// command line, with no parent, owner, CPU usage or memory. Set the
+    /// fields a test cares about with struct update syntax.
+    pub(crate) fn fixture(pid: u32, name: &str) -> Self {
+        ProcessRow {
+            pid,
+            ppid: None,
+            user: None,
+            cpu_usage: 0.0,
+            memory: 0,
+            state: "Sleeping".to_string(),
+            threads: 1,
+            name: name.to_string(),
+            command: name.to_string(),
+        }
+    }
+}
+
+/// Column a process listing can be ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Pid,
+    User,
+    Cpu,
+    Memory,
+    State,
+    Threads,
+    Command,
+}
+
+/// Repeatedly samples the process list of the shared system snapshot.
+///
+/// User names are resolved once when the table is created, so keep the same
+/// table around between samples.
+pub struct ProcessTable {
+    users: Users,
+}
+
+impl Default for ProcessTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcessTable {
+    pub fn new() -> Self {
+        ProcessTable {
+            users: Users::new_with_refreshed_list(),
+        }
+    }
+
+    /// Refreshes the processes and returns one row for every process matching
+    /// `filter`, or all of them without a filter. Threads are skipped.
+    pub fn sample(&mut self, filter: Option<&ProcessMatcher>) -> Result<Vec<ProcessRow>> {
+        let system = refresh_processes_with_details()?;
+        let mut rows = Vec::new();
+        for process in system.processes().values() {
+            if process.thread_kind() == Some(ThreadKind::Userland) {
+                continue;
+            }
+            if filter.is_some_and(|matcher| !matcher.matches(process)) {
+                continue;
+            }
+            let user = process
+                .user_id()
+                .and_then(|uid| self.users.get_user_by_id(uid))
+                .map(|user| user.name().to_string());
+            let name = process.name().to_string_lossy();
+            let command = if process.cmd().is_empty() {
+                format!("[{}]", name)
+            } else {
+                process
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            rows.push(ProcessRow {
+                pid: process.pid().as_u32(),
+                ppid: process.parent().map(|parent| parent.as_u32()),
+                user,
+                cpu_usage: process.cpu_usage(),
+                memory: process.memory(),
+                state: process.status().to_string(),
+                threads: process.tasks().map_or(1, |tasks| tasks.len() + 1),
+                name: name.into_owned(),
+                command,
+            });
+        }
+        Ok(rows)
+    }
+}
+
+/// Sorts `rows` by `key`. Ties are broken by PID so the order stays stable
+/// between refreshes.
+pub fn sort_process_rows(rows: &mut [ProcessRow], key: SortKey, descending: bool) {
+    rows.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Pid => Ordering::Equal,
+            SortKey::User => a.user.cmp(&b.user),
+            SortKey::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            SortKey::Memory => a.memory.cmp(&b.memory),
+            SortKey::State => a.state.cmp(&b.state),
+            SortKey::Threads => a.threads.cmp(&b.threads),
+            SortKey::Command => a.command.cmp(&b.command),
+        }
+        .then(a.pid.cmp(&b.pid));
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProcessRow, ProcessTable, SortKey, sort_process_rows};
+    use crate::models_fn_packer::hunt_helper::{MatchField, MatchMode, ProcessMatcher};
+
+    fn row(pid: u32, cpu_usage: f32, memory: u64) -> ProcessRow {
+        ProcessRow {
+            cpu_usage,
+            memory,
+            ..ProcessRow::fixture(pid, &format!("proc{}", pid))
+        }
+    }
+
+    #[test]
+    fn test_sort_process_rows() {
+        let mut rows = vec![row(3, 1.0, 30), row(1, 5.0, 10), row(2, 5.0, 20)];
+
+        sort_process_rows(&mut rows, SortKey::Cpu, true);
+        let pids: Vec<u32> = rows.iter().map(|row| row.pid).collect();
+        assert_eq!(pids, vec![2, 1, 3]);
+
+        sort_process_rows(&mut rows, SortKey::Memory, false);
+        let pids: Vec<u32> = rows.iter().map(|row| row.pid).collect();
+        assert_eq!(pids, vec![1, 2, 3]);
+
+        sort_process_rows(&mut rows, SortKey::Pid, true);
+        let pids: Vec<u32> = rows.iter().map(|row| row.pid).collect();
+        assert_eq!(pids, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("Failed to spawn sleep process");
+        let pid = child.id();
+
+        let mut table = ProcessTable::new();
+        let all = table.sample(None).unwrap();
+        let matcher = ProcessMatcher::new("sleep 30", MatchMode::Exact, false)
+            .unwrap()
+            .with_fields(&[MatchField::Cmd]);
+        let filtered = table.sample(Some(&matcher)).unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert!(all.iter().any(|row| row.pid == pid));
+        assert!(filtered.len() < all.len());
+        let found = filtered
+            .iter()
+            .find(|row| row.pid == pid)
+            .expect("Spawned process should be listed");
+        assert_eq!(found.command, "sleep 30");
+        assert_eq!(found.threads, 1);
+    }
+}
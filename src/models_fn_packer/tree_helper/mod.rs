@@ -0,0 +1,6 @@
+mod packer;
+
+pub use packer::Ancestors;
+pub use packer::ProcessTree;
+pub use packer::SubtreeTotals;
+pub use packer::Walk;
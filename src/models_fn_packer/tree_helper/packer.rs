@@ -0,0 +1,328 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models_fn_packer::top_helper::ProcessRow;
+
+/// Parent/child forest built from a process listing.
+///
+/// Processes whose parent is not part of the listing become roots, and so
+/// does one process of every parent cycle an inconsistent snapshot may
+/// contain. Children keep the order they had in the listing, so sort the rows
+/// first to get a sorted tree.
+#[derive(Debug, Clone)]
+pub struct ProcessTree {
+    rows: Vec<ProcessRow>,
+    index: HashMap<u32, usize>,
+    children: HashMap<u32, Vec<usize>>,
+    // Position of the parent of every process, `None` for roots.
+    parents: Vec<Option<usize>>,
+    roots: Vec<usize>,
+}
+
+/// CPU and memory of a process and all of its descendants.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubtreeTotals {
+    pub process_count: usize,
+    /// Sum of the CPU usage of every process, in percent.
+    pub cpu_usage: f32,
+    /// Sum of the resident set sizes, in bytes. Pages shared between
+    /// processes are counted once per process.
+    pub memory: u64,
+}
+
+impl ProcessTree {
+    pub fn new(rows: Vec<ProcessRow>) -> Self {
+        let index: HashMap<u32, usize> = rows
+            .iter()
+            .enumerate()
+            .map(|(position, row)| (row.pid, position))
+            .collect();
+        let mut parents: Vec<Option<usize>> = rows
+            .iter()
+            .map(|row| {
+                row.ppid
+                    .filter(|ppid| *ppid != row.pid)
+                    .and_then(|ppid| index.get(&ppid).copied())
+            })
+            .collect();
+        break_cycles(&mut parents);
+
+        let mut children: HashMap<u32, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+        for (position, parent) in parents.iter().enumerate() {
+            match parent {
+                Some(parent) => children
+                    .entry(rows[*parent].pid)
+                    .or_default()
+                    .push(position),
+                None => roots.push(position),
+            }
+        }
+        ProcessTree {
+            rows,
+            index,
+            children,
+            parents,
+            roots,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn get(&self, pid: u32) -> Option<&ProcessRow> {
+        self.index.get(&pid).map(|position| &self.rows[*position])
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = &ProcessRow> {
+        self.roots.iter().map(|position| &self.rows[*position])
+    }
+
+    pub fn children(&self, pid: u32) -> impl Iterator<Item = &ProcessRow> {
+        self.children
+            .get(&pid)
+            .into_iter()
+            .flatten()
+            .map(|position| &self.rows[*position])
+    }
+
+    /// Parent, grandparent and so on up to the root of `pid`.
+    pub fn ancestors(&self, pid: u32) -> Ancestors<'_> {
+        Ancestors {
+            tree: self,
+            current: self.index.get(&pid).copied(),
+        }
+    }
+
+    /// Every process below `pid`, depth first, paired with its depth relative
+    /// to `pid` (children are at depth 1).
+    pub fn descendants(&self, pid: u32) -> Walk<'_> {
+        let stack = self
+            .children
+            .get(&pid)
+            .into_iter()
+            .flatten()
+            .rev()
+            .map(|position| (*position, 1))
+            .collect();
+        Walk {
+            tree: self,
+            stack,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Every process of the forest, depth first, paired with its depth (roots
+    /// are at depth 0).
+    pub fn walk(&self) -> Walk<'_> {
+        let stack = self
+            .roots
+            .iter()
+            .rev()
+            .map(|position| (*position, 0))
+            .collect();
+        Walk {
+            tree: self,
+            stack,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Totals of `pid` and its descendants, `None` if `pid` is not in the tree.
+    pub fn subtree_totals(&self, pid: u32) -> Option<SubtreeTotals> {
+        let root = self.get(pid)?;
+        let mut totals = SubtreeTotals {
+            process_count: 1,
+            cpu_usage: root.cpu_usage,
+            memory: root.memory,
+        };
+        for (_, row) in self.descendants(pid) {
+            totals.process_count += 1;
+            totals.cpu_usage += row.cpu_usage;
+            totals.memory += row.memory;
+        }
+        Some(totals)
+    }
+}
+
+// Detaches one process of every parent cycle from its parent, the first one
+// of the cycle in listing order, so that it becomes a root.
+fn break_cycles(parents: &mut [Option<usize>]) {
+    // 0: not visited yet, 1: on the current chain, 2: done.
+    let mut state = vec![0u8; parents.len()];
+    for start in 0..parents.len() {
+        let mut chain = Vec::new();
+        let mut current = Some(start);
+        while let Some(position) = current {
+            match state[position] {
+                0 => {
+                    state[position] = 1;
+                    chain.push(position);
+                    current = parents[position];
+                }
+                1 => {
+                    let cycle_start = chain.iter().position(|p| *p == position).unwrap_or(0);
+                    let first = *chain[cycle_start..].iter().min().unwrap_or(&position);
+                    parents[first] = None;
+                    break;
+                }
+                _ => break,
+            }
+        }
+        for position in chain {
+            state[position] = 2;
+        }
+    }
+}
+
+/// Iterator returned by [`ProcessTree::ancestors`].
+pub struct Ancestors<'a> {
+    tree: &'a ProcessTree,
+    current: Option<usize>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = &'a ProcessRow;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parent = self.tree.parents[self.current?]?;
+        self.current = Some(parent);
+        Some(&self.tree.rows[parent])
+    }
+}
+
+/// Depth-first iterator returned by [`ProcessTree::walk`] and
+/// [`ProcessTree::descendants`].
+pub struct Walk<'a> {
+    tree: &'a ProcessTree,
+    stack: Vec<(usize, usize)>,
+    // The tree has no cycles, this only makes sure a walk always ends.
+    seen: HashSet<usize>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (usize, &'a ProcessRow);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (position, depth) = loop {
+            let (position, depth) = self.stack.pop()?;
+            if self.seen.insert(position) {
+                break (position, depth);
+            }
+        };
+        let row = &self.tree.rows[position];
+        if let Some(children) = self.tree.children.get(&row.pid) {
+            self.stack
+                .extend(children.iter().rev().map(|child| (*child, depth + 1)));
+        }
+        Some((depth, row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProcessTree, SubtreeTotals};
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    fn row(pid: u32, ppid: Option<u32>, cpu_usage: f32, memory: u64) -> ProcessRow {
+        ProcessRow {
+            ppid,
+            cpu_usage,
+            memory,
+            ..ProcessRow::fixture(pid, &format!("proc{}", pid))
+        }
+    }
+
+    fn sample_tree() -> ProcessTree {
+        ProcessTree::new(vec![
+            row(10, Some(1), 2.0, 200),
+            row(1, None, 1.0, 100),
+            row(11, Some(10), 4.0, 400),
+            row(2, Some(1), 8.0, 800),
+            row(50, Some(999), 16.0, 1600),
+        ])
+    }
+
+    fn pids<'a>(rows: impl Iterator<Item = &'a ProcessRow>) -> Vec<u32> {
+        rows.map(|row| row.pid).collect()
+    }
+
+    #[test]
+    fn test_roots_and_children() {
+        let tree = sample_tree();
+
+        assert_eq!(tree.len(), 5);
+        assert_eq!(pids(tree.roots()), vec![1, 50]);
+        assert_eq!(pids(tree.children(1)), vec![10, 2]);
+        assert_eq!(pids(tree.children(2)), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_walk() {
+        let tree = sample_tree();
+
+        let order: Vec<(usize, u32)> = tree.walk().map(|(depth, row)| (depth, row.pid)).collect();
+        assert_eq!(order, vec![(0, 1), (1, 10), (2, 11), (1, 2), (0, 50)]);
+    }
+
+    #[test]
+    fn test_descendants() {
+        let tree = sample_tree();
+
+        let order: Vec<(usize, u32)> = tree
+            .descendants(1)
+            .map(|(depth, row)| (depth, row.pid))
+            .collect();
+        assert_eq!(order, vec![(1, 10), (2, 11), (1, 2)]);
+        assert_eq!(tree.descendants(11).count(), 0);
+        assert_eq!(tree.descendants(12345).count(), 0);
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let tree = sample_tree();
+
+        assert_eq!(pids(tree.ancestors(11)), vec![10, 1]);
+        assert_eq!(pids(tree.ancestors(1)), Vec::<u32>::new());
+        assert_eq!(pids(tree.ancestors(50)), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_cycle() {
+        let tree = ProcessTree::new(vec![
+            row(3, Some(2), 0.0, 0),
+            row(1, Some(2), 0.0, 0),
+            row(2, Some(1), 0.0, 0),
+            row(4, Some(3), 0.0, 0),
+        ]);
+
+        // The first member of the 1-2 cycle in listing order becomes a root.
+        assert_eq!(pids(tree.roots()), vec![1]);
+        assert_eq!(pids(tree.ancestors(1)), Vec::<u32>::new());
+        assert_eq!(pids(tree.ancestors(4)), vec![3, 2, 1]);
+        let order: Vec<(usize, u32)> = tree.walk().map(|(depth, row)| (depth, row.pid)).collect();
+        assert_eq!(order, vec![(0, 1), (1, 2), (2, 3), (3, 4)]);
+        assert_eq!(tree.descendants(2).count(), 2);
+        assert_eq!(tree.subtree_totals(1).unwrap().process_count, 4);
+    }
+
+    #[test]
+    fn test_subtree_totals() {
+        let tree = sample_tree();
+
+        assert_eq!(
+            tree.subtree_totals(1),
+            Some(SubtreeTotals {
+                process_count: 4,
+                cpu_usage: 15.0,
+                memory: 1500,
+            })
+        );
+        assert_eq!(tree.subtree_totals(12345), None);
+    }
+}
@@ -0,0 +1,9 @@
+mod packer;
+
+pub use packer::AlertEvent;
+pub use packer::AlertKind;
+pub use packer::Measurement;
+pub use packer::Metric;
+pub use packer::Rule;
+pub use packer::Threshold;
+pub use packer::Watcher;
@@ -0,0 +1,449 @@
+use std::time::Duration;
+
+use crate::models_fn_packer::battery_helper::BatteryInfo;
+use crate::models_fn_packer::hunt_helper::ProcessMatcher;
+use crate::models_fn_packer::source_helper::Snapshot;
+use crate::models_fn_packer::top_helper::ProcessRow;
+
+/// Quantity a [`Rule`] watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Metric {
+    /// Usage of all cores combined, in percent.
+    Cpu,
+    /// Used memory, in percent of the installed memory.
+    Memory,
+    /// Used swap, in percent of the swap space.
+    Swap,
+    /// Combined charge of all batteries, in percent.
+    Battery,
+    /// Largest resident set size among the selected processes, in bytes.
+    ProcessRss,
+    /// Highest CPU usage among the selected processes, in percent.
+    ProcessCpu,
+}
+
+impl Metric {
+    /// Whether values are byte counts rather than percentages.
+    pub fn is_bytes(self) -> bool {
+        self == Metric::ProcessRss
+    }
+
+    pub fn is_per_process(self) -> bool {
+        matches!(self, Metric::ProcessRss | Metric::ProcessCpu)
+    }
+}
+
+/// Value a [`Rule`] fires at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    Above(f64),
+    Below(f64),
+}
+
+impl Threshold {
+    pub fn value(self) -> f64 {
+        match self {
+            Threshold::Above(value) | Threshold::Below(value) => value,
+        }
+    }
+
+    fn is_beyond(self, value: f64, limit: f64) -> bool {
+        match self {
+            Threshold::Above(_) => value > limit,
+            Threshold::Below(_) => value < limit,
+        }
+    }
+}
+
+/// An alerting condition on one [`Metric`].
+///
+/// A rule fires once the metric has stayed beyond its threshold for the
+/// configured duration, and resolves once it gets back past the clear
+/// level, which defaults to the threshold itself. Setting the clear level
+/// a bit inside the threshold keeps a value hovering around it from
+/// firing over and over.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    name: String,
+    metric: Metric,
+    threshold: Threshold,
+    clear: f64,
+    duration: Duration,
+    cooldown: Duration,
+    process: Option<ProcessMatcher>,
+    battery_state: Option<String>,
+}
+
+/// Value of a rule's metric at one point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub value: f64,
+    /// Process the value comes from, e.g. `java (pid 1234)`, for
+    /// per-process metrics.
+    pub subject: Option<String>,
+}
+
+impl Rule {
+    /// Creates a rule that fires as soon as the threshold is crossed and can
+    /// fire again right after resolving.
+    pub fn new(name: &str, metric: Metric, threshold: Threshold) -> Self {
+        Rule {
+            name: name.to_string(),
+            metric,
+            threshold,
+            clear: threshold.value(),
+            duration: Duration::ZERO,
+            cooldown: Duration::ZERO,
+            process: None,
+            battery_state: None,
+        }
+    }
+
+    /// Level the metric has to get back past before the rule resolves. Must
+    /// lie on the safe side of the threshold, or at the threshold itself.
+    pub fn with_clear(mut self, clear: f64) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    /// How long the threshold has to stay crossed before the rule fires.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Minimum time between two notifications that the rule fired. A rule
+    /// that fires again within its cooldown stays silent until it resolves,
+    /// and then resolves silently too.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Processes per-process metrics look at. Without a matcher they look at
+    /// every process.
+    pub fn with_process(mut self, matcher: ProcessMatcher) -> Self {
+        self.process = Some(matcher);
+        self
+    }
+
+    /// Only consider the battery charge while at least one battery is in this
+    /// state, e.g. `discharging`.
+    pub fn with_battery_state(mut self, state: &str) -> Self {
+        self.battery_state = Some(state.to_string());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn threshold(&self) -> Threshold {
+        self.threshold
+    }
+
+    pub fn clear(&self) -> f64 {
+        self.clear
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn battery_state(&self) -> Option<&str> {
+        self.battery_state.as_deref()
+    }
+
+    /// Reads the rule's metric from `snapshot`. `None` when there is nothing
+    /// to measure: no swap, no batteries or none in the required state, or no
+    /// selected process.
+    pub fn measure(&self, snapshot: &Snapshot) -> Option<Measurement> {
+        let value = match self.metric {
+            Metric::Cpu => snapshot.cpu.global_usage as f64,
+            Metric::Memory => percent(snapshot.memory.used_memory, snapshot.memory.total_memory)?,
+            Metric::Swap => percent(snapshot.memory.used_swap, snapshot.memory.total_swap)?,
+            Metric::Battery => {
+                if let Some(state) = &self.battery_state
+                    && !snapshot
+                        .batteries
+                        .iter()
+                        .any(|battery| battery.state == *state)
+                {
+                    return None;
+                }
+                let info = BatteryInfo::from_batteries(&snapshot.batteries);
+                if info.energy_full <= 0.0 {
+                    return None;
+                }
+                (info.energy / info.energy_full) as f64 * 100.0
+            }
+            Metric::ProcessRss | Metric::ProcessCpu => {
+                let value = |row: &ProcessRow| {
+                    if self.metric == Metric::ProcessRss {
+                        row.memory as f64
+                    } else {
+                        row.cpu_usage as f64
+                    }
+                };
+                let row = snapshot
+                    .processes
+                    .iter()
+                    .filter(|row| self.process.as_ref().is_none_or(|m| m.matches_row(row)))
+                    .max_by(|a, b| value(a).total_cmp(&value(b)))?;
+                return Some(Measurement {
+                    value: value(row),
+                    subject: Some(format!("{} (pid {})", row.name, row.pid)),
+                });
+            }
+        };
+        Some(Measurement {
+            value,
+            subject: None,
+        })
+    }
+}
+
+fn percent(used: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| used as f64 / total as f64 * 100.0)
+}
+
+/// Whether a rule started or stopped firing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AlertKind {
+    Fired,
+    Resolved,
+}
+
+/// A notification produced by [`Watcher::evaluate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    /// Position of the rule in the list given to [`Watcher::new`].
+    pub rule: usize,
+    pub kind: AlertKind,
+    /// Metric value at the time of the event. `None` when a rule resolves
+    /// because there is nothing left to measure.
+    pub measurement: Option<Measurement>,
+    pub timestamp_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Clear,
+    Pending { since_ms: u64 },
+    Firing { notified: bool },
+}
+
+/// Evaluates a set of [`Rule`]s against successive snapshots.
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    rules: Vec<Rule>,
+    phases: Vec<Phase>,
+    last_fired_ms: Vec<Option<u64>>,
+}
+
+impl Watcher {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Watcher {
+            phases: vec![Phase::Clear; rules.len()],
+            last_fired_ms: vec![None; rules.len()],
+            rules,
+        }
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Whether any rule looks at processes, which are costly to list.
+    pub fn needs_processes(&self) -> bool {
+        self.rules.iter().any(|rule| rule.metric.is_per_process())
+    }
+
+    pub fn needs_batteries(&self) -> bool {
+        self.rules.iter().any(|rule| rule.metric == Metric::Battery)
+    }
+
+    /// Advances every rule to `snapshot.timestamp_ms` and returns the rules
+    /// that fired or resolved, in rule order.
+    pub fn evaluate(&mut self, snapshot: &Snapshot) -> Vec<AlertEvent> {
+        let now_ms = snapshot.timestamp_ms;
+        let mut events = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let measurement = rule.measure(snapshot);
+            let crossed = measurement
+                .as_ref()
+                .is_some_and(|m| rule.threshold.is_beyond(m.value, rule.threshold.value()));
+            let still_active = measurement
+                .as_ref()
+                .is_some_and(|m| rule.threshold.is_beyond(m.value, rule.clear));
+
+            let phase = &mut self.phases[index];
+            if *phase == Phase::Clear && crossed {
+                *phase = Phase::Pending { since_ms: now_ms };
+            }
+            match *phase {
+                Phase::Clear => {}
+                Phase::Pending { .. } if !crossed => *phase = Phase::Clear,
+                Phase::Pending { since_ms } => {
+                    if now_ms.saturating_sub(since_ms) < rule.duration.as_millis() as u64 {
+                        continue;
+                    }
+                    let last_fired_ms = &mut self.last_fired_ms[index];
+                    let notified = last_fired_ms.is_none_or(|fired_ms| {
+                        now_ms.saturating_sub(fired_ms) >= rule.cooldown.as_millis() as u64
+                    });
+                    *phase = Phase::Firing { notified };
+                    if notified {
+                        *last_fired_ms = Some(now_ms);
+                        events.push(AlertEvent {
+                            rule: index,
+                            kind: AlertKind::Fired,
+                            measurement,
+                            timestamp_ms: now_ms,
+                        });
+                    }
+                }
+                Phase::Firing { .. } if still_active => {}
+                Phase::Firing { notified } => {
+                    *phase = Phase::Clear;
+                    if notified {
+                        events.push(AlertEvent {
+                            rule: index,
+                            kind: AlertKind::Resolved,
+                            measurement,
+                            timestamp_ms: now_ms,
+                        });
+                    }
+                }
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{AlertKind, Metric, Rule, Threshold, Watcher};
+    use crate::models_fn_packer::hunt_helper::{MatchMode, ProcessMatcher};
+    use crate::models_fn_packer::source_helper::Snapshot;
+    use crate::models_fn_packer::top_helper::ProcessRow;
+
+    fn cpu_at(timestamp_ms: u64, usage: f32) -> Snapshot {
+        let mut snapshot = Snapshot {
+            timestamp_ms,
+            ..Snapshot::default()
+        };
+        snapshot.cpu.global_usage = usage;
+        snapshot
+    }
+
+    fn kinds(watcher: &mut Watcher, snapshot: &Snapshot) -> Vec<AlertKind> {
+        watcher
+            .evaluate(snapshot)
+            .into_iter()
+            .map(|event| event.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_duration_and_hysteresis() {
+        let rule = Rule::new("busy", Metric::Cpu, Threshold::Above(90.0))
+            .with_clear(80.0)
+            .with_duration(Duration::from_secs(30));
+        let mut watcher = Watcher::new(vec![rule]);
+
+        assert!(kinds(&mut watcher, &cpu_at(0, 95.0)).is_empty());
+        assert!(kinds(&mut watcher, &cpu_at(20_000, 95.0)).is_empty());
+        // Dipping below the threshold restarts the countdown.
+        assert!(kinds(&mut watcher, &cpu_at(25_000, 85.0)).is_empty());
+        assert!(kinds(&mut watcher, &cpu_at(35_000, 95.0)).is_empty());
+        assert_eq!(
+            kinds(&mut watcher, &cpu_at(65_000, 95.0)),
+            vec![AlertKind::Fired]
+        );
+        // Between the clear level and the threshold the rule keeps firing.
+        assert!(kinds(&mut watcher, &cpu_at(70_000, 85.0)).is_empty());
+        assert_eq!(
+            kinds(&mut watcher, &cpu_at(75_000, 70.0)),
+            vec![AlertKind::Resolved]
+        );
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let rule = Rule::new("busy", Metric::Cpu, Threshold::Above(90.0))
+            .with_cooldown(Duration::from_secs(60));
+        let mut watcher = Watcher::new(vec![rule]);
+
+        assert_eq!(
+            kinds(&mut watcher, &cpu_at(0, 95.0)),
+            vec![AlertKind::Fired]
+        );
+        assert_eq!(
+            kinds(&mut watcher, &cpu_at(10_000, 50.0)),
+            vec![AlertKind::Resolved]
+        );
+        // Fires again within the cooldown: both transitions stay silent.
+        assert!(kinds(&mut watcher, &cpu_at(20_000, 95.0)).is_empty());
+        assert!(kinds(&mut watcher, &cpu_at(30_000, 50.0)).is_empty());
+        assert_eq!(
+            kinds(&mut watcher, &cpu_at(60_000, 95.0)),
+            vec![AlertKind::Fired]
+        );
+    }
+
+    #[test]
+    fn test_process_rss() {
+        let row = |pid: u32, name: &str, memory: u64| ProcessRow {
+            memory,
+            ..ProcessRow::fixture(pid, name)
+        };
+        let snapshot = Snapshot {
+            processes: vec![
+                row(1, "java", 1 << 30),
+                row(2, "java", 5 << 30),
+                row(3, "vim", 8 << 30),
+            ],
+            ..Snapshot::default()
+        };
+        let matcher = ProcessMatcher::new("java", MatchMode::Exact, false).unwrap();
+        let rule = Rule::new(
+            "java",
+            Metric::ProcessRss,
+            Threshold::Above((4u64 << 30) as f64),
+        )
+        .with_process(matcher);
+
+        let measurement = rule.measure(&snapshot).unwrap();
+        assert_eq!(measurement.value, (5u64 << 30) as f64);
+        assert_eq!(measurement.subject.as_deref(), Some("java (pid 2)"));
+
+        let mut watcher = Watcher::new(vec![rule]);
+        assert!(watcher.needs_processes());
+        assert!(!watcher.needs_batteries());
+        let events = watcher.evaluate(&snapshot);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].rule, 0);
+
+        // The process exiting resolves the rule.
+        let events = watcher.evaluate(&Snapshot::default());
+        assert_eq!(events[0].kind, AlertKind::Resolved);
+        assert_eq!(events[0].measurement, None);
+    }
+
+    #[test]
+    fn test_swap_without_swap_space() {
+        let rule = Rule::new("swap", Metric::Swap, Threshold::Above(50.0));
+        assert_eq!(rule.measure(&Snapshot::default()), None);
+    }
+}
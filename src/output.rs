@@ -0,0 +1,205 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text
+    Text,
+    Json,
+    /// One row per item for lists, flattened `key,value` rows otherwise
+    Csv,
+    Yaml,
+}
+
+/// Prints `document` in one of the machine-readable formats. Must not be
+/// called with `OutputFormat::Text`, which every command renders itself.
+pub fn print_document<T: Serialize>(format: OutputFormat, document: &T) {
+    let rendered = match format {
+        OutputFormat::Text => unreachable!("text output is rendered by each command"),
+        OutputFormat::Json => serde_json::to_string_pretty(document).map_err(|err| err.to_string()),
+        OutputFormat::Yaml => serde_yaml::to_string(document).map_err(|err| err.to_string()),
+        OutputFormat::Csv => serde_json::to_value(document)
+            .map_err(|err| err.to_string())
+            .and_then(|value| to_csv(&value)),
+    };
+    match rendered {
+        Ok(rendered) => println!("{}", rendered.trim_end()),
+        Err(err) => {
+            eprintln!("Failed to render output: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Lists become a table with one row per element and one column per
+// flattened field, anything else `key,value` rows.
+fn to_csv(value: &Value) -> Result<String, String> {
+    let (header, records) = match value {
+        Value::Array(values) => {
+            let elements: Vec<Vec<(String, String)>> = values
+                .iter()
+                .map(|value| {
+                    let mut rows = Vec::new();
+                    flatten(value, String::new(), true, &mut rows);
+                    rows
+                })
+                .collect();
+            // Fields in first-seen order, elements may not all have the same.
+            let mut header: Vec<String> = Vec::new();
+            for (key, _) in elements.iter().flatten() {
+                if !header.contains(key) {
+                    header.push(key.clone());
+                }
+            }
+            let records = elements
+                .into_iter()
+                .map(|rows| {
+                    header
+                        .iter()
+                        .map(|column| {
+                            rows.iter()
+                                .find(|(key, _)| key == column)
+                                .map(|(_, value)| value.clone())
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .collect();
+            let header = header
+                .into_iter()
+                .map(|key| {
+                    if key.is_empty() {
+                        "value".to_string()
+                    } else {
+                        key
+                    }
+                })
+                .collect();
+            (header, records)
+        }
+        _ => {
+            let mut rows = Vec::new();
+            flatten(value, String::new(), false, &mut rows);
+            let records: Vec<Vec<String>> = rows
+                .into_iter()
+                .map(|(key, value)| vec![key, value])
+                .collect();
+            (vec!["key".to_string(), "value".to_string()], records)
+        }
+    };
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(&header)
+        .map_err(|err| err.to_string())?;
+    for record in records {
+        writer
+            .write_record(&record)
+            .map_err(|err| err.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|err| err.to_string())?;
+    String::from_utf8(bytes).map_err(|err| err.to_string())
+}
+
+// Turns nested objects and arrays into dotted keys, e.g. `cpu.cores.0.usage`.
+// With `join_scalars`, arrays of plain values such as a command line become a
+// single space-separated cell instead, so table columns do not depend on the
+// length of each element's arrays.
+fn flatten(value: &Value, key: String, join_scalars: bool, rows: &mut Vec<(String, String)>) {
+    let child_key = |child: &str| {
+        if key.is_empty() {
+            child.to_string()
+        } else {
+            format!("{}.{}", key, child)
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (child, value) in map {
+                flatten(value, child_key(child), join_scalars, rows);
+            }
+        }
+        Value::Array(values)
+            if join_scalars && values.iter().all(|value| scalar(value).is_some()) =>
+        {
+            let joined: Vec<String> = values.iter().filter_map(scalar).collect();
+            rows.push((key, joined.join(" ")));
+        }
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                flatten(value, child_key(&index.to_string()), join_scalars, rows);
+            }
+        }
+        _ => rows.push((key, scalar(value).unwrap_or_default())),
+    }
+}
+
+// The cell text of a value that is neither an object nor an array.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        Value::Object(_) | Value::Array(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten() {
+        let value = json!({
+            "battery": null,
+            "cpu": {"cores": [{"name": "cpu0", "usage": 1.5}], "global_usage": 2.0},
+        });
+        let mut rows = Vec::new();
+        super::flatten(&value, String::new(), false, &mut rows);
+
+        let rows: Vec<(&str, &str)> = rows.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("battery", ""),
+                ("cpu.cores.0.name", "cpu0"),
+                ("cpu.cores.0.usage", "1.5"),
+                ("cpu.global_usage", "2.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_csv_quotes_values() {
+        let csv = super::to_csv(&json!({"cmd": "a,b"})).unwrap();
+
+        assert_eq!(csv, "key,value\ncmd,\"a,b\"\n");
+    }
+
+    #[test]
+    fn test_to_csv_array() {
+        let csv = super::to_csv(&json!([
+            {"pid": 1, "name": "init", "user": {"name": "root"}},
+            {"pid": 42, "name": "a,b", "extra": true},
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            csv,
+            "name,pid,user.name,extra\ninit,1,root,\n\"a,b\",42,,true\n"
+        );
+        assert_eq!(super::to_csv(&json!([1, 2])).unwrap(), "value\n1\n2\n");
+    }
+
+    #[test]
+    fn test_to_csv_array_joins_scalar_lists() {
+        let csv = super::to_csv(&json!([
+            {"pid": 1, "cmd": ["init"]},
+            {"pid": 42, "cmd": ["java", "-jar", "app.jar"]},
+            {"pid": 43, "cmd": []},
+        ]))
+        .unwrap();
+
+        assert_eq!(csv, "cmd,pid\ninit,1\njava -jar app.jar,42\n,43\n");
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use proc_hunter::models_fn_packer::{SnapshotWriter, SystemSource};
+
+use crate::{or_exit, parse_duration, parse_interval};
+
+#[derive(Args)]
+pub struct RecordArgs {
+    /// Time between snapshots, e.g. 500ms, 1s or 1m
+    #[arg(long, value_parser = parse_interval, default_value = "1s")]
+    interval: Duration,
+
+    /// Log file to write, replaced if it exists
+    #[arg(short, long)]
+    out: PathBuf,
+
+    /// Stop after this long instead of running until interrupted
+    #[arg(long, value_parser = parse_duration)]
+    duration: Option<Duration>,
+
+    /// Stop after this many snapshots
+    #[arg(long)]
+    count: Option<u64>,
+}
+
+pub fn run(args: &RecordArgs, source: &mut dyn SystemSource) {
+    let mut writer = or_exit(SnapshotWriter::create(&args.out, args.interval));
+    let started = Instant::now();
+    // A duration too long to represent is as good as none.
+    let deadline = args
+        .duration
+        .and_then(|duration| started.checked_add(duration));
+    let mut next_tick = started;
+    loop {
+        or_exit(
+            source
+                .snapshot()
+                .and_then(|snapshot| writer.write(&snapshot)),
+        );
+        if args.count.is_some_and(|count| writer.records() >= count) {
+            break;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+        // Schedule from the start so that slow snapshots do not add up.
+        let Some(tick) = next_tick.checked_add(args.interval) else {
+            break;
+        };
+        next_tick = tick;
+        if deadline.is_some_and(|deadline| next_tick > deadline) {
+            break;
+        }
+        std::thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+    }
+    eprintln!(
+        "Recorded {} snapshots to {}",
+        writer.records(),
+        args.out.display()
+    );
+}
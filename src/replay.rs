@@ -0,0 +1,194 @@
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, ValueEnum};
+use proc_hunter::models_fn_packer::{BatteryRateSmoother, DEFAULT_RATE_SMOOTHING};
+use proc_hunter::models_fn_packer::{Snapshot, SnapshotReader, sort_process_rows};
+use serde::Serialize;
+
+use crate::output::{OutputFormat, print_document};
+use crate::top::{SortKeyArg, render_frame, terminal_width};
+use crate::{InfoData, format_bytes, or_exit, parse_duration, print_info, print_info_document};
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Log written by `record`
+    log: PathBuf,
+
+    /// Only show the snapshot at this offset from the start, e.g. 90s
+    #[arg(long, value_parser = parse_duration)]
+    at: Option<Duration>,
+
+    /// What to show for each snapshot
+    #[arg(long, value_enum, default_value_t = View::Info)]
+    view: View,
+
+    /// Playback speed relative to the recording, 0 for no pauses
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Column the top view sorts by
+    #[arg(short, long, value_enum, default_value_t = SortKeyArg::Cpu)]
+    sort: SortKeyArg,
+
+    /// Maximum number of rows of the top view, 0 for no limit
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum View {
+    Info,
+    Top,
+    Tree,
+}
+
+#[derive(Args)]
+pub struct QueryArgs {
+    /// Log written by `record`
+    log: PathBuf,
+
+    /// Process to report on
+    #[arg(short, long)]
+    pid: u32,
+}
+
+/// One tick of `query` output.
+#[derive(Serialize)]
+struct ProcessSample {
+    timestamp_ms: u64,
+    /// Milliseconds since the first snapshot of the log.
+    offset_ms: u64,
+    cpu_usage: f32,
+    memory: u64,
+    state: String,
+}
+
+pub fn replay(args: &ReplayArgs, format: OutputFormat) {
+    let mut reader = or_exit(SnapshotReader::open(&args.log));
+    let mut smoother = BatteryRateSmoother::new(DEFAULT_RATE_SMOOTHING);
+    let mut start = None;
+    let mut previous: Option<Snapshot> = None;
+    for snapshot in reader.by_ref() {
+        let snapshot = or_exit(snapshot);
+        let start = *start.get_or_insert(snapshot.timestamp_ms);
+        let offset = Duration::from_millis(snapshot.timestamp_ms.saturating_sub(start));
+        // Show the last snapshot taken at or before the offset, with the
+        // battery rate smoothed up to that snapshot only.
+        if args.at.is_some_and(|at| offset > at) {
+            break;
+        }
+        smoother.update(&snapshot.batteries);
+        if args.at.is_none() {
+            if let Some(previous) = &previous
+                && args.speed > 0.0
+            {
+                let gap = snapshot.timestamp_ms.saturating_sub(previous.timestamp_ms);
+                std::thread::sleep(Duration::from_millis(gap).div_f64(args.speed));
+            }
+            show(args, &snapshot, &smoother, offset, format);
+        }
+        previous = Some(snapshot);
+    }
+    warn_skipped(&reader);
+    if args.at.is_some() {
+        match &previous {
+            Some(snapshot) => {
+                let offset = snapshot
+                    .timestamp_ms
+                    .saturating_sub(start.unwrap_or(snapshot.timestamp_ms));
+                show(
+                    args,
+                    snapshot,
+                    &smoother,
+                    Duration::from_millis(offset),
+                    format,
+                );
+            }
+            None => {
+                eprintln!("The log has no snapshots");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn show(
+    args: &ReplayArgs,
+    snapshot: &Snapshot,
+    smoother: &BatteryRateSmoother,
+    offset: Duration,
+    format: OutputFormat,
+) {
+    if args.view == View::Info && format != OutputFormat::Text {
+        print_info_document(&InfoData::from_snapshot(snapshot, smoother), false, format);
+        return;
+    }
+    println!("=== +{:.1}s ===", offset.as_secs_f64());
+    match args.view {
+        View::Info => print_info(&InfoData::from_snapshot(snapshot, smoother), false),
+        View::Top => {
+            let mut rows = snapshot.processes.clone();
+            sort_process_rows(&mut rows, args.sort.into(), true);
+            let system = Ok((snapshot.cpu.clone(), snapshot.memory));
+            print!(
+                "{}",
+                render_frame(&rows, &system, None, args.limit, terminal_width())
+            );
+        }
+        View::Tree => or_exit(crate::tree::print_tree(
+            snapshot.processes.clone(),
+            None,
+            false,
+            false,
+        )),
+    }
+}
+
+pub fn query(args: &QueryArgs, format: OutputFormat) {
+    let mut reader = or_exit(SnapshotReader::open(&args.log));
+    let mut start = None;
+    let mut samples = Vec::new();
+    for snapshot in reader.by_ref() {
+        let snapshot = or_exit(snapshot);
+        let start = *start.get_or_insert(snapshot.timestamp_ms);
+        if let Some(row) = snapshot.processes.iter().find(|row| row.pid == args.pid) {
+            samples.push(ProcessSample {
+                timestamp_ms: snapshot.timestamp_ms,
+                offset_ms: snapshot.timestamp_ms.saturating_sub(start),
+                cpu_usage: row.cpu_usage,
+                memory: row.memory,
+                state: row.state.clone(),
+            });
+        }
+    }
+    warn_skipped(&reader);
+    if format != OutputFormat::Text {
+        print_document(format, &samples);
+    } else if !samples.is_empty() {
+        println!("{:>10} {:>6} {:>9}  STATE", "TIME", "CPU%", "RSS");
+        for sample in &samples {
+            println!(
+                "{:>9.1}s {:>6.1} {:>9}  {}",
+                sample.offset_ms as f64 / 1000.0,
+                sample.cpu_usage,
+                format_bytes(sample.memory),
+                sample.state
+            );
+        }
+    }
+    if samples.is_empty() {
+        eprintln!("Process {} does not appear in the log", args.pid);
+        std::process::exit(1);
+    }
+}
+
+fn warn_skipped<R: BufRead>(reader: &SnapshotReader<R>) {
+    if reader.skipped() > 0 {
+        eprintln!(
+            "Skipped {} damaged or unreadable records of the log",
+            reader.skipped()
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use clap::Args;
+use proc_hunter::models_fn_packer::{ProcessMatcher, ProcessRow, SystemSource, render_metrics};
+
+use crate::MatchOptions;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// Scrapes are answered one at a time, so a client that stalls must not hold
+// up the next ones for long.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to accept scrapes on
+    #[arg(long, default_value = "127.0.0.1:9925")]
+    listen: String,
+
+    /// Also export CPU and memory of every process matching this pattern
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    #[command(flatten)]
+    match_options: MatchOptions,
+}
+
+pub fn run(args: &ServeArgs, source: &mut dyn SystemSource) {
+    let matcher = args
+        .pattern
+        .as_deref()
+        .map(|pattern| args.match_options.to_matcher(pattern));
+    let listener = TcpListener::bind(&args.listen).unwrap_or_else(|err| {
+        eprintln!("Error: cannot listen on {}: {}", args.listen, err);
+        std::process::exit(1);
+    });
+    eprintln!("Serving metrics on http://{}/metrics", args.listen);
+    serve(&listener, source, matcher.as_ref());
+}
+
+// Answers scrapes one at a time; every scrape takes a fresh snapshot, so CPU
+// usage is averaged over the time since the previous scrape.
+fn serve(listener: &TcpListener, source: &mut dyn SystemSource, matcher: Option<&ProcessMatcher>) {
+    for stream in listener.incoming() {
+        let result =
+            stream.and_then(|stream| handle_with_timeout(stream, CLIENT_TIMEOUT, source, matcher));
+        if let Err(err) = result {
+            eprintln!("Warning: {}", err);
+        }
+    }
+}
+
+fn handle_with_timeout(
+    stream: TcpStream,
+    timeout: Duration,
+    source: &mut dyn SystemSource,
+    matcher: Option<&ProcessMatcher>,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    handle(stream, source, matcher)
+}
+
+fn handle(
+    mut stream: TcpStream,
+    source: &mut dyn SystemSource,
+    matcher: Option<&ProcessMatcher>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers are not needed, but must be read before answering.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+    let (status, content_type, body) = if path != "/metrics" {
+        ("404 Not Found", "text/plain", "Not found\n".to_string())
+    } else if method != "GET" && method != "HEAD" {
+        (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        )
+    } else {
+        match source.snapshot() {
+            Ok(snapshot) => {
+                let processes: Vec<ProcessRow> = match matcher {
+                    Some(matcher) => snapshot
+                        .processes
+                        .iter()
+                        .filter(|row| matcher.matches_row(row))
+                        .cloned()
+                        .collect(),
+                    None => Vec::new(),
+                };
+                (
+                    "200 OK",
+                    CONTENT_TYPE,
+                    render_metrics(&snapshot, &processes),
+                )
+            }
+            Err(err) => (
+                "500 Internal Server Error",
+                "text/plain",
+                format!("{}\n", err),
+            ),
+        }
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    if status.starts_with("405") {
+        write!(stream, "Allow: GET, HEAD\r\n")?;
+    }
+    write!(stream, "\r\n")?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes())?;
+    }
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Duration;
+
+    use proc_hunter::models_fn_packer::{FixtureSource, MatchMode, ProcessMatcher, ProcessRow};
+
+    fn request(text: &'static str) -> String {
+        let row = ProcessRow {
+            pid: 7,
+            ppid: None,
+            user: None,
+            cpu_usage: 1.0,
+            memory: 4096,
+            state: "Sleeping".to_string(),
+            threads: 1,
+            name: "java".to_string(),
+            command: "java".to_string(),
+        };
+        let mut source = FixtureSource::default();
+        source.snapshot.processes = vec![row];
+        let matcher = ProcessMatcher::new("java", MatchMode::Exact, false).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(text.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        super::handle(stream, &mut source, Some(&matcher)).unwrap();
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn test_metrics() {
+        let response = request("GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.contains("\r\n\r\n# HELP proc_hunter_cpu_usage_percent"));
+        assert!(response.contains("proc_hunter_processes 1\n"));
+        assert!(
+            response.contains(
+                "proc_hunter_process_resident_memory_bytes{pid=\"7\",name=\"java\"} 4096\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_idle_client_times_out() {
+        let mut source = FixtureSource::default();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        let result =
+            super::handle_with_timeout(stream, Duration::from_millis(50), &mut source, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_unknown_path() {
+        let response = request("GET / HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let response = request("POST /metrics HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use clap::{Args, ValueEnum};
+use proc_hunter::models_fn_packer::sort_process_rows;
+use proc_hunter::models_fn_packer::{CpuInfo, MemoryInfo, get_cpu_info, get_memory_info};
+use proc_hunter::models_fn_packer::{ProcMemory, ProcessRow, ProcessTable, SortKey};
+
+use crate::mem::{format_pss_columns, read_process_memory};
+use crate::{MatchOptions, format_bytes};
+
+// Clear the screen and move the cursor to the top-left corner.
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+#[derive(Args)]
+pub struct TopArgs {
+    /// Refresh interval in milliseconds
+    #[arg(short = 'd', long, default_value_t = 1000)]
+    interval_ms: u64,
+
+    /// Column to sort by
+    #[arg(short, long, value_enum, default_value_t = SortKeyArg::Cpu)]
+    sort: SortKeyArg,
+
+    /// Sort in ascending order instead of descending
+    #[arg(long, default_value_t = false)]
+    ascending: bool,
+
+    /// Maximum number of rows, 0 for no limit
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+
+    /// Stop after this many refreshes
+    #[arg(long)]
+    iterations: Option<u64>,
+
+    /// Only show processes matching this pattern
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// Add PSS and USS columns, read from /proc/<pid>/smaps_rollup (Linux)
+    #[arg(long, default_value_t = false)]
+    pss: bool,
+
+    #[command(flatten)]
+    match_options: MatchOptions,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SortKeyArg {
+    Pid,
+    User,
+    Cpu,
+    Mem,
+    State,
+    Threads,
+    Command,
+}
+
+impl From<SortKeyArg> for SortKey {
+    fn from(key: SortKeyArg) -> Self {
+        match key {
+            SortKeyArg::Pid => SortKey::Pid,
+            SortKeyArg::User => SortKey::User,
+            SortKeyArg::Cpu => SortKey::Cpu,
+            SortKeyArg::Mem => SortKey::Memory,
+            SortKeyArg::State => SortKey::State,
+            SortKeyArg::Threads => SortKey::Threads,
+            SortKeyArg::Command => SortKey::Command,
+        }
+    }
+}
+
+pub fn run(args: &TopArgs) {
+    let matcher = args
+        .pattern
+        .as_ref()
+        .map(|pattern| args.match_options.to_matcher(pattern));
+    let interactive = std::io::stdout().is_terminal();
+    let mut table = ProcessTable::new();
+    let mut iteration = 0;
+    loop {
+        let mut rows = crate::or_exit(table.sample(matcher.as_ref()));
+        sort_process_rows(&mut rows, args.sort.into(), !args.ascending);
+        let system = get_cpu_info().and_then(|cpu| Ok((cpu, get_memory_info()?)));
+        let memory = args.pss.then(|| {
+            let shown = if args.limit == 0 {
+                rows.len()
+            } else {
+                args.limit
+            };
+            read_process_memory(rows.iter().take(shown).map(|row| row.pid))
+        });
+        let frame = render_frame(
+            &rows,
+            &system,
+            memory.as_ref(),
+            args.limit,
+            terminal_width(),
+        );
+
+        let mut stdout = std::io::stdout().lock();
+        if interactive {
+            let _ = write!(stdout, "{}", CLEAR_SCREEN);
+        } else if iteration > 0 {
+            let _ = writeln!(stdout);
+        }
+        if write!(stdout, "{}", frame)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            // The reader went away, e.g. `proc_hunter top | head`.
+            return;
+        }
+        drop(stdout);
+
+        iteration += 1;
+        if args
+            .iterations
+            .is_some_and(|iterations| iteration >= iterations)
+        {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(args.interval_ms));
+    }
+}
+
+/// Renders the header and the first `limit` of `rows`, which are expected
+/// to be sorted already. PSS and USS columns are added when `memory` is
+/// given.
+pub fn render_frame(
+    rows: &[ProcessRow],
+    system: &proc_hunter::Result<(CpuInfo, MemoryInfo)>,
+    memory: Option<&HashMap<u32, ProcMemory>>,
+    limit: usize,
+    width: Option<usize>,
+) -> String {
+    let summary = match system {
+        Ok((cpu, memory)) => format!(
+            "{} processes, CPU {:.1}%, memory {} / {}, swap {} / {}",
+            rows.len(),
+            cpu.global_usage,
+            format_bytes(memory.used_memory),
+            format_bytes(memory.total_memory),
+            format_bytes(memory.used_swap),
+            format_bytes(memory.total_swap),
+        ),
+        Err(err) => {
+            format!(
+                "{} processes, CPU and memory not available ({})",
+                rows.len(),
+                err
+            )
+        }
+    };
+    let mut lines = vec![
+        summary,
+        String::new(),
+        format!(
+            "{:>7} {:<12} {:>6} {:>9}{} {:<10} {:>4}  COMMAND",
+            "PID",
+            "USER",
+            "CPU%",
+            "MEM",
+            if memory.is_some() {
+                format!(" {:>9} {:>9}", "PSS", "USS")
+            } else {
+                String::new()
+            },
+            "STATE",
+            "THR"
+        ),
+    ];
+    let shown = if limit == 0 { rows.len() } else { limit };
+    for row in rows.iter().take(shown) {
+        lines.push(format!(
+            "{:>7} {:<12} {:>6.1} {:>9}{} {:<10} {:>4}  {}",
+            row.pid,
+            truncate(row.user.as_deref().unwrap_or("?"), 12),
+            row.cpu_usage,
+            format_bytes(row.memory),
+            memory.map_or(String::new(), |memory| format!(
+                " {}",
+                format_pss_columns(memory, row.pid)
+            )),
+            truncate(&row.state, 10),
+            row.threads,
+            row.command
+        ));
+    }
+    let mut frame = String::new();
+    for line in lines {
+        match width {
+            Some(width) => frame.push_str(&truncate(&line, width)),
+            None => frame.push_str(&line),
+        }
+        frame.push('\n');
+    }
+    frame
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+#[cfg(unix)]
+pub fn terminal_width() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes into the winsize struct we pass in.
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    if result == 0 && size.ws_col > 0 {
+        Some(size.ws_col as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+pub fn terminal_width() -> Option<usize> {
+    None
+}
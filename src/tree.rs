@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+
+use clap::Args;
+use proc_hunter::Error;
+use proc_hunter::models_fn_packer::{ProcessRow, ProcessTree, SystemSource};
+
+use crate::format_bytes;
+
+#[derive(Args)]
+pub struct TreeArgs {
+    /// Only show the ancestors and descendants of this process
+    pid: Option<u32>,
+
+    /// Draw the connectors with plain ASCII characters
+    #[arg(long, default_value_t = false)]
+    ascii: bool,
+
+    /// Merge identical sibling processes into a single `N*[name]` line
+    #[arg(short, long, default_value_t = false)]
+    collapse: bool,
+}
+
+struct Connectors {
+    branch: &'static str,
+    last: &'static str,
+    pipe: &'static str,
+    blank: &'static str,
+}
+
+const UNICODE: Connectors = Connectors {
+    branch: "├─ ",
+    last: "└─ ",
+    pipe: "│  ",
+    blank: "   ",
+};
+
+const ASCII: Connectors = Connectors {
+    branch: "|- ",
+    last: "`- ",
+    pipe: "|  ",
+    blank: "   ",
+};
+
+pub fn run(args: &TreeArgs, source: &mut dyn SystemSource) {
+    let rows = crate::or_exit(source.processes());
+    crate::or_exit(print_tree(rows, args.pid, args.ascii, args.collapse));
+}
+
+/// Fails with [`Error::ProcessVanished`] if `pid` is not among `rows`.
+pub fn print_tree(
+    mut rows: Vec<ProcessRow>,
+    pid: Option<u32>,
+    ascii: bool,
+    collapse: bool,
+) -> proc_hunter::Result<()> {
+    rows.sort_by_key(|row| row.pid);
+    let tree = ProcessTree::new(rows);
+    let connectors = if ascii { &ASCII } else { &UNICODE };
+    for line in render(&tree, pid, connectors, collapse)? {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn render(
+    tree: &ProcessTree,
+    pid: Option<u32>,
+    connectors: &Connectors,
+    collapse: bool,
+) -> proc_hunter::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    // ProcessTree already breaks parent cycles; this only guarantees the
+    // recursion ends.
+    let mut seen = HashSet::new();
+    let Some(pid) = pid else {
+        for root in tree.roots() {
+            seen.insert(root.pid);
+            lines.push(label(tree, &[root]));
+            render_children(
+                tree, root.pid, "", connectors, collapse, &mut seen, &mut lines,
+            );
+        }
+        return Ok(lines);
+    };
+    let Some(target) = tree.get(pid) else {
+        return Err(Error::ProcessVanished(pid));
+    };
+
+    let mut ancestors: Vec<&ProcessRow> = tree.ancestors(pid).collect();
+    ancestors.reverse();
+    let mut prefix = String::new();
+    for (depth, ancestor) in ancestors.iter().enumerate() {
+        let connector = if depth == 0 { "" } else { connectors.last };
+        lines.push(format!(
+            "{}{}{}",
+            prefix,
+            connector,
+            label(tree, &[ancestor])
+        ));
+        if depth > 0 {
+            prefix.push_str(connectors.blank);
+        }
+    }
+    if ancestors.is_empty() {
+        lines.push(label(tree, &[target]));
+    } else {
+        lines.push(format!(
+            "{}{}{}",
+            prefix,
+            connectors.last,
+            label(tree, &[target])
+        ));
+        prefix.push_str(connectors.blank);
+    }
+    seen.insert(pid);
+    render_children(
+        tree, pid, &prefix, connectors, collapse, &mut seen, &mut lines,
+    );
+    Ok(lines)
+}
+
+fn render_children(
+    tree: &ProcessTree,
+    pid: u32,
+    prefix: &str,
+    connectors: &Connectors,
+    collapse: bool,
+    seen: &mut HashSet<u32>,
+    lines: &mut Vec<String>,
+) {
+    let children: Vec<&ProcessRow> = tree
+        .children(pid)
+        .filter(|child| seen.insert(child.pid))
+        .collect();
+    let groups = group_siblings(tree, &children, collapse);
+    for (index, group) in groups.iter().enumerate() {
+        let is_last = index + 1 == groups.len();
+        let connector = if is_last {
+            connectors.last
+        } else {
+            connectors.branch
+        };
+        lines.push(format!("{}{}{}", prefix, connector, label(tree, group)));
+        if let [child] = group.as_slice() {
+            let continuation = if is_last {
+                connectors.blank
+            } else {
+                connectors.pipe
+            };
+            let child_prefix = format!("{}{}", prefix, continuation);
+            render_children(
+                tree,
+                child.pid,
+                &child_prefix,
+                connectors,
+                collapse,
+                seen,
+                lines,
+            );
+        }
+    }
+}
+
+// Groups childless siblings sharing a name, keeping first-seen order.
+fn group_siblings<'a>(
+    tree: &ProcessTree,
+    siblings: &[&'a ProcessRow],
+    collapse: bool,
+) -> Vec<Vec<&'a ProcessRow>> {
+    let mut groups: Vec<Vec<&ProcessRow>> = Vec::new();
+    for sibling in siblings {
+        let is_leaf = tree.children(sibling.pid).next().is_none();
+        let existing = groups.iter_mut().find(|group| {
+            collapse
+                && is_leaf
+                && group[0].name == sibling.name
+                && tree.children(group[0].pid).next().is_none()
+        });
+        match existing {
+            Some(group) => group.push(sibling),
+            None => groups.push(vec![sibling]),
+        }
+    }
+    groups
+}
+
+fn label(tree: &ProcessTree, group: &[&ProcessRow]) -> String {
+    let mut cpu_usage = 0.0;
+    let mut memory = 0;
+    for row in group {
+        if let Some(totals) = tree.subtree_totals(row.pid) {
+            cpu_usage += totals.cpu_usage;
+            memory += totals.memory;
+        }
+    }
+    let name = match group {
+        [row] => format!("{}({})", row.name, row.pid),
+        _ => format!("{}*[{}]", group.len(), group[0].name),
+    };
+    format!(
+        "{}  [cpu {:.1}% mem {}]",
+        name,
+        cpu_usage,
+        format_bytes(memory)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_hunter::models_fn_packer::{ProcessRow, ProcessTree};
+
+    fn row(pid: u32, ppid: Option<u32>, name: &str) -> ProcessRow {
+        ProcessRow {
+            pid,
+            ppid,
+            user: None,
+            cpu_usage: 1.0,
+            memory: 1024,
+            state: "Sleeping".to_string(),
+            threads: 1,
+            name: name.to_string(),
+            command: name.to_string(),
+        }
+    }
+
+    fn sample_tree() -> ProcessTree {
+        ProcessTree::new(vec![
+            row(1, None, "init"),
+            row(2, Some(1), "master"),
+            row(3, Some(2), "worker"),
+            row(4, Some(2), "worker"),
+            row(5, Some(2), "worker"),
+            row(6, Some(1), "shell"),
+        ])
+    }
+
+    #[test]
+    fn test_render_forest() {
+        let lines = super::render(&sample_tree(), None, &super::UNICODE, false).unwrap();
+
+        assert_eq!(
+            lines,
+            vec![
+                "init(1)  [cpu 6.0% mem 6.0KiB]",
+                "├─ master(2)  [cpu 4.0% mem 4.0KiB]",
+                "│  ├─ worker(3)  [cpu 1.0% mem 1.0KiB]",
+                "│  ├─ worker(4)  [cpu 1.0% mem 1.0KiB]",
+                "│  └─ worker(5)  [cpu 1.0% mem 1.0KiB]",
+                "└─ shell(6)  [cpu 1.0% mem 1.0KiB]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_collapsed_ascii() {
+        let lines = super::render(&sample_tree(), None, &super::ASCII, true).unwrap();
+
+        assert_eq!(
+            lines,
+            vec![
+                "init(1)  [cpu 6.0% mem 6.0KiB]",
+                "|- master(2)  [cpu 4.0% mem 4.0KiB]",
+                "|  `- 3*[worker]  [cpu 3.0% mem 3.0KiB]",
+                "`- shell(6)  [cpu 1.0% mem 1.0KiB]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_pid() {
+        let lines = super::render(&sample_tree(), Some(4), &super::UNICODE, false).unwrap();
+
+        assert_eq!(
+            lines,
+            vec![
+                "init(1)  [cpu 6.0% mem 6.0KiB]",
+                "└─ master(2)  [cpu 4.0% mem 4.0KiB]",
+                "   └─ worker(4)  [cpu 1.0% mem 1.0KiB]",
+            ]
+        );
+        assert!(matches!(
+            super::render(&sample_tree(), Some(99), &super::UNICODE, false),
+            Err(proc_hunter::Error::ProcessVanished(99))
+        ));
+    }
+
+    #[test]
+    fn test_render_cycle() {
+        let tree = ProcessTree::new(vec![row(7, Some(8), "a"), row(8, Some(7), "b")]);
+
+        assert_eq!(
+            super::render(&tree, Some(8), &super::ASCII, false).unwrap(),
+            vec![
+                "a(7)  [cpu 2.0% mem 2.0KiB]",
+                "`- b(8)  [cpu 1.0% mem 1.0KiB]"
+            ]
+        );
+        assert_eq!(
+            super::render(&tree, Some(7), &super::ASCII, false).unwrap(),
+            vec![
+                "a(7)  [cpu 2.0% mem 2.0KiB]",
+                "`- b(8)  [cpu 1.0% mem 1.0KiB]"
+            ]
+        );
+    }
+}
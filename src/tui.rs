@@ -0,0 +1,530 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use proc_hunter::models_fn_packer::ProcessTree;
+use proc_hunter::models_fn_packer::{BatteryInfo, CpuInfo, MemoryInfo, ProcessRow, ProcessTable};
+use proc_hunter::models_fn_packer::{Signal, SortKey, send_signal, signal_name, sort_process_rows};
+use proc_hunter::models_fn_packer::{get_battery_info, get_cpu_info, get_memory_info};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Block, Borders, Clear, Gauge, Paragraph, Row, Sparkline, Table, TableState,
+};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::format_bytes;
+
+// Samples kept per core, enough for a wide terminal.
+const HISTORY_LEN: usize = 240;
+const MAX_CORE_ROWS: usize = 8;
+
+const SORT_KEYS: [(SortKey, &str); 7] = [
+    (SortKey::Cpu, "CPU%"),
+    (SortKey::Memory, "MEM"),
+    (SortKey::Pid, "PID"),
+    (SortKey::User, "USER"),
+    (SortKey::State, "STATE"),
+    (SortKey::Threads, "THR"),
+    (SortKey::Command, "COMMAND"),
+];
+
+const SIGNAL_KEYS: [(char, Signal); 6] = [
+    ('t', Signal::Term),
+    ('k', Signal::Kill),
+    ('h', Signal::Hangup),
+    ('i', Signal::Interrupt),
+    ('s', Signal::Stop),
+    ('c', Signal::Continue),
+];
+
+#[derive(Args)]
+pub struct TuiArgs {
+    /// Refresh interval in milliseconds
+    #[arg(short = 'd', long, default_value_t = 1000)]
+    interval_ms: u64,
+}
+
+pub fn run(args: &TuiArgs) {
+    let mut terminal = ratatui::init();
+    let result = App::new().run(&mut terminal, Duration::from_millis(args.interval_ms));
+    ratatui::restore();
+    if let Err(err) = result {
+        eprintln!("Terminal error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+// The signal modes keep the process that was selected when the popup opened,
+// as the table may refresh and re-sort in the meantime.
+enum Mode {
+    Normal,
+    Search,
+    Signal {
+        pid: u32,
+        name: String,
+    },
+    Confirm {
+        pid: u32,
+        name: String,
+        signal: Signal,
+    },
+}
+
+struct App {
+    table: ProcessTable,
+    // Unfiltered result of the last sample.
+    sampled: Vec<ProcessRow>,
+    // Rows as displayed, with their depth in tree view.
+    rows: Vec<(ProcessRow, usize)>,
+    state: TableState,
+    cpu: CpuInfo,
+    cpu_history: Vec<VecDeque<u64>>,
+    memory: MemoryInfo,
+    battery: Option<BatteryInfo>,
+    sort: usize,
+    descending: bool,
+    tree: bool,
+    mode: Mode,
+    search: String,
+    status: String,
+    should_quit: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        App {
+            table: ProcessTable::new(),
+            sampled: Vec::new(),
+            rows: Vec::new(),
+            state: TableState::default().with_selected(Some(0)),
+            cpu: CpuInfo::default(),
+            cpu_history: Vec::new(),
+            memory: MemoryInfo::default(),
+            battery: None,
+            sort: 0,
+            descending: true,
+            tree: false,
+            mode: Mode::Normal,
+            search: String::new(),
+            status: String::new(),
+            should_quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal, interval: Duration) -> std::io::Result<()> {
+        self.refresh();
+        let mut last_refresh = Instant::now();
+        while !self.should_quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            let timeout = interval.saturating_sub(last_refresh.elapsed());
+            if event::poll(timeout)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key);
+            }
+            if last_refresh.elapsed() >= interval {
+                self.refresh();
+                last_refresh = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    // Keeps the previous figures of anything that cannot be read and reports
+    // the failure on the status line.
+    fn refresh(&mut self) {
+        match get_cpu_info() {
+            Ok(cpu) => self.cpu = cpu,
+            Err(err) => self.status = format!("CPU not available: {}", err),
+        }
+        self.cpu_history
+            .resize_with(self.cpu.logical_core_count(), VecDeque::new);
+        for (history, core) in self.cpu_history.iter_mut().zip(&self.cpu.cores) {
+            if history.len() == HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(core.usage.round() as u64);
+        }
+        match get_memory_info() {
+            Ok(memory) => self.memory = memory,
+            Err(err) => self.status = format!("Memory not available: {}", err),
+        }
+        self.battery = get_battery_info().ok();
+        match self.table.sample(None) {
+            Ok(rows) => self.sampled = rows,
+            Err(err) => self.status = format!("Processes not available: {}", err),
+        }
+        self.arrange();
+    }
+
+    // Applies search, sorting and tree view to the last sample, keeping the
+    // selected process selected.
+    fn arrange(&mut self) {
+        let selected_pid = self.selected_pid();
+        let search = self.search.to_lowercase();
+        let mut rows: Vec<ProcessRow> = self
+            .sampled
+            .iter()
+            .filter(|row| {
+                search.is_empty()
+                    || row.pid.to_string() == search
+                    || row.command.to_lowercase().contains(&search)
+            })
+            .cloned()
+            .collect();
+        sort_process_rows(&mut rows, SORT_KEYS[self.sort].0, self.descending);
+        self.rows = if self.tree {
+            ProcessTree::new(rows)
+                .walk()
+                .map(|(depth, row)| (row.clone(), depth))
+                .collect()
+        } else {
+            rows.into_iter().map(|row| (row, 0)).collect()
+        };
+
+        let index = selected_pid
+            .and_then(|pid| self.rows.iter().position(|(row, _)| row.pid == pid))
+            .unwrap_or(0);
+        self.state.select(Some(index));
+    }
+
+    fn selected_pid(&self) -> Option<u32> {
+        self.state
+            .selected()
+            .and_then(|index| self.rows.get(index))
+            .map(|(row, _)| row.pid)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.should_quit = true;
+            return;
+        }
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_key(key.code),
+            Mode::Search => {
+                self.mode = Mode::Search;
+                self.handle_search_key(key.code)
+            }
+            Mode::Signal { pid, name } => self.handle_signal_key(key.code, pid, name),
+            Mode::Confirm { pid, name, signal } => {
+                if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                    self.send(pid, &name, signal);
+                } else {
+                    self.status = "Cancelled".to_string();
+                }
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, code: KeyCode) {
+        let last = self.rows.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Down => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::PageDown => self.state.select(Some((selected + 20).min(last))),
+            KeyCode::PageUp => self.state.select(Some(selected.saturating_sub(20))),
+            KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('s') => {
+                self.sort = (self.sort + 1) % SORT_KEYS.len();
+                self.arrange();
+            }
+            KeyCode::Char('r') => {
+                self.descending = !self.descending;
+                self.arrange();
+            }
+            KeyCode::Char('t') => {
+                self.tree = !self.tree;
+                self.arrange();
+            }
+            KeyCode::Char('k') => {
+                if let Some((row, _)) = self.state.selected().and_then(|index| self.rows.get(index))
+                {
+                    self.mode = Mode::Signal {
+                        pid: row.pid,
+                        name: row.name.clone(),
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Enter => self.mode = Mode::Normal,
+            KeyCode::Esc => {
+                self.search.clear();
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Backspace => {
+                self.search.pop();
+            }
+            KeyCode::Char(c) => self.search.push(c),
+            _ => return,
+        }
+        self.arrange();
+    }
+
+    fn handle_signal_key(&mut self, code: KeyCode, pid: u32, name: String) {
+        let KeyCode::Char(c) = code else {
+            return;
+        };
+        let Some((_, signal)) = SIGNAL_KEYS.iter().find(|(key, _)| *key == c) else {
+            return;
+        };
+        if matches!(signal, Signal::Kill | Signal::Term) {
+            self.mode = Mode::Confirm {
+                pid,
+                name,
+                signal: *signal,
+            };
+        } else {
+            self.send(pid, &name, *signal);
+        }
+    }
+
+    // Refuses when the PID now belongs to another program.
+    fn send(&mut self, pid: u32, name: &str, signal: Signal) {
+        if self
+            .sampled
+            .iter()
+            .any(|row| row.pid == pid && row.name != name)
+        {
+            self.status = format!("{} is no longer {}, nothing sent", pid, name);
+            return;
+        }
+        match send_signal(&[pid], signal) {
+            Ok(reports) => {
+                for report in reports {
+                    self.status = format!(
+                        "SIG{} to {}: {}",
+                        signal_name(signal),
+                        report.pid,
+                        report.outcome
+                    );
+                }
+            }
+            Err(err) => self.status = format!("SIG{} to {}: {}", signal_name(signal), pid, err),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let core_rows = self.cpu.logical_core_count().clamp(1, MAX_CORE_ROWS);
+        let [cpu_area, gauges_area, table_area, footer_area] = Layout::vertical([
+            Constraint::Length(core_rows as u16 + 2),
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        self.draw_cpu(frame, cpu_area, core_rows);
+        self.draw_gauges(frame, gauges_area);
+        self.draw_processes(frame, table_area);
+        self.draw_footer(frame, footer_area);
+        match &self.mode {
+            Mode::Signal { pid, name } => self.draw_signal_popup(frame, *pid, name),
+            Mode::Confirm { pid, name, signal } => {
+                self.draw_confirm_popup(frame, *pid, name, *signal)
+            }
+            Mode::Normal | Mode::Search => {}
+        }
+    }
+
+    fn draw_cpu(&self, frame: &mut Frame, area: Rect, core_rows: usize) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" CPU {:.1}% ", self.cpu.global_usage));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let columns = self.cpu.logical_core_count().div_ceil(core_rows).max(1);
+        let column_areas = Layout::horizontal(vec![Constraint::Fill(1); columns]).split(inner);
+        for (index, core) in self.cpu.cores.iter().enumerate() {
+            let column_area = column_areas[index / core_rows];
+            let row_area = Rect {
+                y: column_area.y + (index % core_rows) as u16,
+                height: 1,
+                ..column_area
+            };
+            let [label_area, spark_area] =
+                Layout::horizontal([Constraint::Length(13), Constraint::Fill(1)]).areas(row_area);
+            frame.render_widget(
+                Paragraph::new(format!("{:<6}{:>5.1}%", core.name, core.usage)),
+                label_area,
+            );
+            let history = &self.cpu_history[index];
+            let skip = history.len().saturating_sub(spark_area.width as usize);
+            let data: Vec<u64> = history.iter().skip(skip).copied().collect();
+            frame.render_widget(
+                Sparkline::default()
+                    .data(&data)
+                    .max(100)
+                    .style(Style::default().fg(Color::Green)),
+                spark_area,
+            );
+        }
+    }
+
+    fn draw_gauges(&self, frame: &mut Frame, area: Rect) {
+        let [memory_area, swap_area, battery_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        frame.render_widget(
+            usage_gauge(
+                " Memory ",
+                self.memory.used_memory,
+                self.memory.total_memory,
+            ),
+            memory_area,
+        );
+        frame.render_widget(
+            usage_gauge(" Swap ", self.memory.used_swap, self.memory.total_swap),
+            swap_area,
+        );
+
+        let block = Block::default().borders(Borders::ALL).title(" Battery ");
+        let battery = match self.battery.and_then(|battery| {
+            battery
+                .state_of_charge()
+                .map(|charge| (charge, battery.energy_rate))
+        }) {
+            Some((charge, rate)) => Gauge::default()
+                .block(block)
+                .gauge_style(Style::default().fg(Color::Yellow))
+                .ratio(charge.clamp(0.0, 1.0) as f64)
+                .label(format!("{:.0}% at {:.1} W", charge * 100.0, rate)),
+            None => Gauge::default().block(block).label("not available"),
+        };
+        frame.render_widget(battery, battery_area);
+    }
+
+    fn draw_processes(&mut self, frame: &mut Frame, area: Rect) {
+        let (sort_key, sort_name) = SORT_KEYS[self.sort];
+        let arrow = if self.descending { "▼" } else { "▲" };
+        let header = ["PID", "USER", "CPU%", "MEM", "STATE", "THR", "COMMAND"].map(|name| {
+            if name == sort_name {
+                format!("{}{}", name, arrow)
+            } else {
+                name.to_string()
+            }
+        });
+        let rows = self.rows.iter().map(|(row, depth)| {
+            let command = if *depth == 0 {
+                row.command.clone()
+            } else {
+                format!("{}└─ {}", "   ".repeat(depth - 1), row.command)
+            };
+            Row::new([
+                row.pid.to_string(),
+                row.user.clone().unwrap_or_else(|| "?".to_string()),
+                format!("{:.1}", row.cpu_usage),
+                format_bytes(row.memory),
+                row.state.clone(),
+                row.threads.to_string(),
+                command,
+            ])
+        });
+        let mut title = format!(" Processes ({}) ", self.rows.len());
+        if self.tree {
+            title.push_str("[tree] ");
+        }
+        if sort_key != SortKey::Cpu || !self.descending {
+            title.push_str(&format!("[sort {}] ", sort_name));
+        }
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(7),
+                Constraint::Length(10),
+                Constraint::Length(6),
+                Constraint::Length(9),
+                Constraint::Length(10),
+                Constraint::Length(4),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.state);
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let text = match self.mode {
+            Mode::Search => format!("Search: {}_", self.search),
+            _ => {
+                let mut text = "q quit  / search  s sort  r reverse  t tree  k signal".to_string();
+                if !self.search.is_empty() {
+                    text.push_str(&format!("  | filter: {}", self.search));
+                }
+                if !self.status.is_empty() {
+                    text.push_str(&format!("  | {}", self.status));
+                }
+                text
+            }
+        };
+        frame.render_widget(Paragraph::new(text), area);
+    }
+
+    fn draw_signal_popup(&self, frame: &mut Frame, pid: u32, name: &str) {
+        let mut lines: Vec<Line> = SIGNAL_KEYS
+            .iter()
+            .map(|(key, signal)| Line::from(format!(" {}  SIG{}", key, signal_name(*signal))))
+            .collect();
+        lines.push(Line::from(" Esc cancel"));
+        draw_popup(frame, format!(" Signal {} ({}) ", pid, name), lines, 24);
+    }
+
+    fn draw_confirm_popup(&self, frame: &mut Frame, pid: u32, name: &str, signal: Signal) {
+        let question = format!(" Send SIG{} to {} ({})? ", signal_name(signal), pid, name);
+        let width = question.chars().count() as u16 + 2;
+        let lines = vec![
+            Line::from(question),
+            Line::from(" y confirm, any key cancels"),
+        ];
+        draw_popup(frame, " Confirm ".to_string(), lines, width);
+    }
+}
+
+fn draw_popup(frame: &mut Frame, title: String, lines: Vec<Line>, width: u16) {
+    let area = frame.area();
+    let width = width.max(title.chars().count() as u16 + 2).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+        popup,
+    );
+}
+
+fn usage_gauge(title: &str, used: u64, total: u64) -> Gauge<'_> {
+    let ratio = if total > 0 {
+        (used as f64 / total as f64).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .gauge_style(Style::default().fg(Color::Cyan))
+        .ratio(ratio)
+        .label(format!("{} / {}", format_bytes(used), format_bytes(total)))
+}
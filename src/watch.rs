@@ -0,0 +1,572 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use proc_hunter::models_fn_packer::{AlertEvent, AlertKind, Metric, Rule, Snapshot, Threshold};
+use proc_hunter::models_fn_packer::{MatchField, MatchMode, ProcessMatcher};
+use proc_hunter::models_fn_packer::{SystemSource, Watcher, now_ms};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{format_bytes, format_duration, or_exit, parse_duration, parse_interval, parse_size};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// TOML file with the rules to evaluate
+    #[arg(long)]
+    rules: PathBuf,
+
+    /// Time between evaluations, overriding the rules file
+    #[arg(long, value_parser = parse_interval)]
+    interval: Option<Duration>,
+
+    /// Only check the rules file and exit
+    #[arg(long, default_value_t = false)]
+    check: bool,
+}
+
+// Layout of the rules file:
+//
+//     interval = "5s"
+//
+//     [[rule]]
+//     name = "java-rss"
+//     metric = "process_rss"
+//     process = "java"
+//     above = "4GiB"
+//     clear = "3.5GiB"
+//     for = "1m"
+//     cooldown = "30m"
+//
+//     [[rule.action]]
+//     type = "webhook"
+//     url = "http://localhost:8080/alerts"
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    interval: Option<String>,
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleConfig {
+    name: String,
+    metric: Metric,
+    above: Option<Quantity>,
+    below: Option<Quantity>,
+    clear: Option<Quantity>,
+    #[serde(rename = "for")]
+    duration: Option<String>,
+    cooldown: Option<String>,
+    /// Glob on the process name, for per-process metrics.
+    process: Option<String>,
+    battery_state: Option<String>,
+    #[serde(default, rename = "action")]
+    actions: Vec<Action>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Quantity {
+    Number(f64),
+    Text(String),
+}
+
+/// What to do when a rule fires or resolves.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum Action {
+    /// Print a line on stderr.
+    Stderr,
+    /// Log to the system logger.
+    Syslog,
+    /// Run a shell command, with the event described in `PROC_HUNTER_*`
+    /// environment variables.
+    Command { command: String },
+    /// POST the event as JSON. Only plain `http://` URLs are supported.
+    Webhook { url: String },
+}
+
+struct WatchConfig {
+    interval: Duration,
+    rules: Vec<Rule>,
+    actions: Vec<Vec<Action>>,
+}
+
+pub fn run(args: &WatchArgs, source: &mut dyn SystemSource) {
+    let config = or_exit(load_rules(&args.rules));
+    if args.check {
+        println!(
+            "{}: {} rules, evaluated every {:?}",
+            args.rules.display(),
+            config.rules.len(),
+            args.interval.unwrap_or(config.interval)
+        );
+        return;
+    }
+    if config
+        .actions
+        .iter()
+        .flatten()
+        .any(|a| *a == Action::Syslog)
+    {
+        open_syslog();
+    }
+    let interval = args.interval.unwrap_or(config.interval);
+    let mut watcher = Watcher::new(config.rules);
+    let mut next_tick = Instant::now();
+    loop {
+        match read_snapshot(source, &watcher) {
+            Ok(snapshot) => {
+                for event in watcher.evaluate(&snapshot) {
+                    let rule = &watcher.rules()[event.rule];
+                    for action in &config.actions[event.rule] {
+                        dispatch(action, rule, &event);
+                    }
+                }
+            }
+            Err(err) => eprintln!("Warning: {}", err),
+        }
+        next_tick += interval;
+        std::thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+    }
+}
+
+// Reads only what the rules look at: listing processes is much more costly
+// than refreshing CPU and memory.
+fn read_snapshot(
+    source: &mut dyn SystemSource,
+    watcher: &Watcher,
+) -> proc_hunter::Result<Snapshot> {
+    Ok(Snapshot {
+        timestamp_ms: now_ms(),
+        cpu: source.cpu()?,
+        memory: source.memory()?,
+        processes: if watcher.needs_processes() {
+            source.processes()?
+        } else {
+            Vec::new()
+        },
+        batteries: if watcher.needs_batteries() {
+            source.batteries().unwrap_or_default()
+        } else {
+            Vec::new()
+        },
+    })
+}
+
+fn load_rules(path: &Path) -> proc_hunter::Result<WatchConfig> {
+    let text = std::fs::read_to_string(path)?;
+    parse_rules(&text)
+        .map_err(|err| proc_hunter::Error::Parse(format!("{}: {}", path.display(), err)))
+}
+
+fn parse_rules(text: &str) -> Result<WatchConfig, String> {
+    let file: RulesFile = toml::from_str(text).map_err(|err| err.message().to_string())?;
+    let interval = match &file.interval {
+        Some(interval) => parse_duration(interval)?,
+        None => DEFAULT_INTERVAL,
+    };
+    if interval.is_zero() {
+        return Err("interval must be positive".to_string());
+    }
+    let mut rules = Vec::new();
+    let mut actions = Vec::new();
+    for config in file.rules {
+        if rules.iter().any(|rule: &Rule| rule.name() == config.name) {
+            return Err(format!("duplicate rule {:?}", config.name));
+        }
+        let rule = build_rule(&config).map_err(|err| format!("rule {:?}: {}", config.name, err))?;
+        rules.push(rule);
+        actions.push(if config.actions.is_empty() {
+            vec![Action::Stderr]
+        } else {
+            config.actions
+        });
+    }
+    Ok(WatchConfig {
+        interval,
+        rules,
+        actions,
+    })
+}
+
+fn build_rule(config: &RuleConfig) -> Result<Rule, String> {
+    let metric = config.metric;
+    let threshold = match (&config.above, &config.below) {
+        (Some(above), None) => Threshold::Above(parse_quantity(above, metric)?),
+        (None, Some(below)) => Threshold::Below(parse_quantity(below, metric)?),
+        _ => return Err("exactly one of above and below is required".to_string()),
+    };
+    let mut rule = Rule::new(&config.name, metric, threshold);
+    if let Some(clear) = &config.clear {
+        let clear = parse_quantity(clear, metric)?;
+        let inside = match threshold {
+            Threshold::Above(value) => clear <= value,
+            Threshold::Below(value) => clear >= value,
+        };
+        if !inside {
+            return Err("clear must not lie beyond the threshold".to_string());
+        }
+        rule = rule.with_clear(clear);
+    }
+    if let Some(duration) = &config.duration {
+        rule = rule.with_duration(parse_duration(duration)?);
+    }
+    if let Some(cooldown) = &config.cooldown {
+        rule = rule.with_cooldown(parse_duration(cooldown)?);
+    }
+    match &config.process {
+        Some(_) if !metric.is_per_process() => {
+            return Err("process only applies to process_rss and process_cpu".to_string());
+        }
+        Some(pattern) => {
+            let matcher = ProcessMatcher::new(pattern, MatchMode::Glob, false)
+                .map_err(|err| err.to_string())?;
+            rule = rule.with_process(matcher.with_fields(&[MatchField::Name]));
+        }
+        None => {}
+    }
+    match &config.battery_state {
+        Some(_) if metric != Metric::Battery => {
+            return Err("battery_state only applies to battery".to_string());
+        }
+        Some(state) => rule = rule.with_battery_state(&state.to_lowercase()),
+        None => {}
+    }
+    for action in &config.actions {
+        if let Action::Webhook { url } = action {
+            parse_http_url(url)?;
+        }
+    }
+    Ok(rule)
+}
+
+// Percentages for every metric but process_rss, which takes sizes like
+// `512MiB` or plain byte counts.
+fn parse_quantity(quantity: &Quantity, metric: Metric) -> Result<f64, String> {
+    let text = match quantity {
+        Quantity::Number(value) => return Ok(*value),
+        Quantity::Text(text) => text.trim(),
+    };
+    if metric.is_bytes() {
+        return parse_size(text).map(|bytes| bytes as f64);
+    }
+    text.strip_suffix('%')
+        .unwrap_or(text)
+        .trim()
+        .parse()
+        .map_err(|_| format!("{:?} is not a percentage", text))
+}
+
+fn metric_name(metric: Metric) -> &'static str {
+    match metric {
+        Metric::Cpu => "cpu",
+        Metric::Memory => "memory",
+        Metric::Swap => "swap",
+        Metric::Battery => "battery",
+        Metric::ProcessRss => "process_rss",
+        Metric::ProcessCpu => "process_cpu",
+    }
+}
+
+fn format_value(metric: Metric, value: f64) -> String {
+    if metric.is_bytes() {
+        format_bytes(value as u64)
+    } else {
+        format!("{:.1}%", value)
+    }
+}
+
+fn describe(rule: &Rule, event: &AlertEvent) -> String {
+    let kind = match event.kind {
+        AlertKind::Fired => "fired",
+        AlertKind::Resolved => "resolved",
+    };
+    let mut text = format!("{} {}: {}", rule.name(), kind, metric_name(rule.metric()));
+    let Some(measurement) = &event.measurement else {
+        text.push_str(" has nothing to measure");
+        return text;
+    };
+    if let Some(subject) = &measurement.subject {
+        text.push_str(&format!(" of {}", subject));
+    }
+    text.push_str(&format!(
+        " at {}",
+        format_value(rule.metric(), measurement.value)
+    ));
+    if event.kind == AlertKind::Fired {
+        let (side, limit) = match rule.threshold() {
+            Threshold::Above(limit) => ("above", limit),
+            Threshold::Below(limit) => ("below", limit),
+        };
+        text.push_str(&format!(
+            ", {} {}",
+            side,
+            format_value(rule.metric(), limit)
+        ));
+        if let Some(state) = rule.battery_state() {
+            text.push_str(&format!(" while {}", state));
+        }
+        let duration = rule.duration();
+        if duration >= Duration::from_secs(60) {
+            text.push_str(&format!(" for {}", format_duration(duration.as_secs())));
+        } else if !duration.is_zero() {
+            text.push_str(&format!(" for {}s", duration.as_secs()));
+        }
+    }
+    text
+}
+
+fn dispatch(action: &Action, rule: &Rule, event: &AlertEvent) {
+    let message = describe(rule, event);
+    match action {
+        Action::Stderr => eprintln!("{}", message),
+        Action::Syslog => write_syslog(event.kind, &message),
+        Action::Command { command } => {
+            let mut child = Command::new("sh");
+            child
+                .arg("-c")
+                .arg(command)
+                .env("PROC_HUNTER_RULE", rule.name())
+                .env("PROC_HUNTER_EVENT", kind_name(event.kind))
+                .env("PROC_HUNTER_MESSAGE", &message);
+            if let Some(measurement) = &event.measurement {
+                child.env("PROC_HUNTER_VALUE", measurement.value.to_string());
+            }
+            match child.spawn() {
+                // Reap the child without holding up the next evaluation.
+                Ok(mut child) => {
+                    std::thread::spawn(move || child.wait());
+                }
+                Err(err) => eprintln!("Warning: cannot run {:?}: {}", command, err),
+            }
+        }
+        Action::Webhook { url } => {
+            let body = json!({
+                "rule": rule.name(),
+                "event": kind_name(event.kind),
+                "metric": metric_name(rule.metric()),
+                "value": event.measurement.as_ref().map(|m| m.value),
+                "subject": event.measurement.as_ref().and_then(|m| m.subject.clone()),
+                "message": message,
+                "timestamp_ms": event.timestamp_ms,
+            })
+            .to_string();
+            let url = url.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = post_json(&url, &body) {
+                    eprintln!("Warning: webhook {}: {}", url, err);
+                }
+            });
+        }
+    }
+}
+
+fn kind_name(kind: AlertKind) -> &'static str {
+    match kind {
+        AlertKind::Fired => "fired",
+        AlertKind::Resolved => "resolved",
+    }
+}
+
+// Splits `http://host[:port][/path]` into the address to connect to, the
+// host header and the request path.
+fn parse_http_url(url: &str) -> Result<(String, String, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("{:?}: only http:// webhooks are supported", url))?;
+    let (host, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err(format!("{:?}: missing host", url));
+    }
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+    Ok((address, host.to_string(), path.to_string()))
+}
+
+fn post_json(url: &str, body: &str) -> Result<(), String> {
+    let (address, host, path) = parse_http_url(url)?;
+    let mut stream = TcpStream::connect(&address).map_err(|err| err.to_string())?;
+    let _ = stream.set_read_timeout(Some(WEBHOOK_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(WEBHOOK_TIMEOUT));
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )
+    .map_err(|err| err.to_string())?;
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    let status = response.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        Some(_) => Err(status.to_string()),
+        None => Err("no response".to_string()),
+    }
+}
+
+#[cfg(unix)]
+fn open_syslog() {
+    // SAFETY: the identifier is a static string, as openlog keeps the pointer.
+    unsafe { libc::openlog(c"proc_hunter".as_ptr(), libc::LOG_PID, libc::LOG_DAEMON) };
+}
+
+#[cfg(not(unix))]
+fn open_syslog() {}
+
+#[cfg(unix)]
+fn write_syslog(kind: AlertKind, message: &str) {
+    let priority = match kind {
+        AlertKind::Fired => libc::LOG_WARNING,
+        AlertKind::Resolved => libc::LOG_NOTICE,
+    };
+    let Ok(message) = std::ffi::CString::new(message) else {
+        return;
+    };
+    // SAFETY: both strings are NUL-terminated, and the message goes through
+    // a "%s" format so that it cannot inject conversions.
+    unsafe { libc::syslog(priority, c"%s".as_ptr(), message.as_ptr()) };
+}
+
+#[cfg(not(unix))]
+fn write_syslog(_kind: AlertKind, message: &str) {
+    eprintln!("{}", message);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use proc_hunter::models_fn_packer::{AlertEvent, AlertKind, Measurement, Threshold};
+
+    use super::Action;
+
+    const RULES: &str = r#"
+interval = "10s"
+
+[[rule]]
+name = "busy"
+metric = "cpu"
+above = "90%"
+clear = 80
+for = "30s"
+cooldown = "10m"
+
+[[rule]]
+name = "java-rss"
+metric = "process_rss"
+process = "java"
+above = "4GiB"
+
+[[rule.action]]
+type = "command"
+command = "logger java"
+
+[[rule.action]]
+type = "webhook"
+url = "http://localhost:9000/hook"
+
+[[rule]]
+name = "low-battery"
+metric = "battery"
+below = 15
+battery_state = "discharging"
+action = [{ type = "syslog" }]
+"#;
+
+    #[test]
+    fn test_parse_rules() {
+        let config = super::parse_rules(RULES).unwrap();
+
+        assert_eq!(config.interval, Duration::from_secs(10));
+        assert_eq!(config.rules.len(), 3);
+        let busy = &config.rules[0];
+        assert_eq!(busy.threshold(), Threshold::Above(90.0));
+        assert_eq!(busy.clear(), 80.0);
+        assert_eq!(busy.duration(), Duration::from_secs(30));
+        assert_eq!(config.actions[0], vec![Action::Stderr]);
+        assert_eq!(
+            config.rules[1].threshold(),
+            Threshold::Above((4u64 << 30) as f64)
+        );
+        assert_eq!(
+            config.actions[1][1],
+            Action::Webhook {
+                url: "http://localhost:9000/hook".to_string()
+            }
+        );
+        assert_eq!(config.rules[2].battery_state(), Some("discharging"));
+        assert_eq!(config.actions[2], vec![Action::Syslog]);
+
+        let event = AlertEvent {
+            rule: 1,
+            kind: AlertKind::Fired,
+            measurement: Some(Measurement {
+                value: (5u64 << 30) as f64,
+                subject: Some("java (pid 12)".to_string()),
+            }),
+            timestamp_ms: 0,
+        };
+        assert_eq!(
+            super::describe(&config.rules[1], &event),
+            "java-rss fired: process_rss of java (pid 12) at 5.0GiB, above 4.0GiB"
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let error = |text: &str| super::parse_rules(text).err().unwrap();
+
+        assert!(error("[[rule]]\nname = \"a\"\nmetric = \"cpu\"\n").contains("above and below"));
+        assert!(
+            error("[[rule]]\nname = \"a\"\nmetric = \"cpu\"\nabove = \"4GiB\"\n")
+                .contains("not a percentage")
+        );
+        assert!(
+            error("[[rule]]\nname = \"a\"\nmetric = \"cpu\"\nabove = 90\nclear = 95\n")
+                .contains("clear")
+        );
+        assert!(error("[[rule]]\nname = \"a\"\nmetric = \"load\"\nabove = 1\n").contains("load"));
+        assert!(
+            error(
+                "[[rule]]\nname = \"a\"\nmetric = \"cpu\"\nabove = 1\n\
+                 [[rule.action]]\ntype = \"webhook\"\nurl = \"https://example.com\"\n"
+            )
+            .contains("http://")
+        );
+    }
+
+    #[test]
+    fn test_parse_http_url() {
+        assert_eq!(
+            super::parse_http_url("http://localhost:9000/hook?x=1"),
+            Ok((
+                "localhost:9000".to_string(),
+                "localhost:9000".to_string(),
+                "/hook?x=1".to_string()
+            ))
+        );
+        assert_eq!(
+            super::parse_http_url("http://example.com").unwrap().0,
+            "example.com:80"
+        );
+    }
+}